@@ -0,0 +1,55 @@
+/// Every way to pick `size` of some items, keeping their original order.
+#[derive(Debug, Clone)]
+pub struct Combinations<T> {
+    items: Vec<T>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<T: Clone> Combinations<T> {
+    pub fn new(items: Vec<T>, size: usize) -> Self {
+        Combinations {
+            done: size > items.len(),
+            indices: (0..size).collect(),
+            items,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for Combinations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+
+        let combination = self
+            .indices
+            .iter()
+            .map(|&index| self.items[index].clone())
+            .collect();
+
+        // bump the rightmost index that still has room to move, then pack
+        // the ones after it in right behind it
+        let size = self.indices.len();
+        let last = self.items.len() - size;
+
+        match (0..size).rev().find(|&i| self.indices[i] < last + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+
+                for j in i + 1..size {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+
+        Some(combination)
+    }
+}
+
+pub fn combinations<T: Clone>(items: &[T], size: usize) -> Combinations<T> {
+    Combinations::new(items.to_vec(), size)
+}
@@ -0,0 +1,16 @@
+//! Iterators over search spaces, and a way to search them on every core.
+//!
+//! Day 7 tries every order of phase settings and day 2 tries every noun and
+//! verb, which are permutations and a cartesian product. Each iterator here
+//! keeps its state in a couple of small vectors rather than building up
+//! partial results.
+
+mod combinations;
+mod parallel;
+mod permutations;
+mod product;
+
+pub use combinations::{combinations, Combinations};
+pub use parallel::{parallel_find_map, parallel_map, parallel_map_with, threads};
+pub use permutations::{permutations, permute, Permutations};
+pub use product::{product, Product};
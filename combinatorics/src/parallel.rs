@@ -0,0 +1,108 @@
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+/// How many threads the parallel helpers use.
+pub fn threads() -> usize {
+    thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+}
+
+/// Like `items.iter().map(f).collect()`, spread over every core. Items are
+/// dealt out round-robin so a run of slow ones doesn't land on one thread.
+pub fn parallel_map<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    parallel_map_with(items, || (), |_, item| f(item))
+}
+
+/// Like `parallel_map`, but every thread calls `init` once to make some
+/// state of its own, like a cache, and hands it to `f` for each item.
+pub fn parallel_map_with<T, S, R>(
+    items: &[T],
+    init: impl Fn() -> S + Sync,
+    f: impl Fn(&mut S, &T) -> R + Sync,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let threads = threads().min(items.len()).max(1);
+    let (init, f) = (&init, &f);
+
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|first| {
+                scope.spawn(move || {
+                    let mut state = init();
+
+                    (first..items.len())
+                        .step_by(threads)
+                        .map(|index| (index, f(&mut state, &items[index])))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        // pass a worker's panic on as it was, rather than as a failed join
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Like `items.iter().find_map(f)`, spread over every core. Threads stop as
+/// soon as nothing they have left could beat what's been found, and the
+/// answer is always the one for the earliest item, same as the serial
+/// version.
+pub fn parallel_find_map<T, R>(items: &[T], f: impl Fn(&T) -> Option<R> + Sync) -> Option<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let threads = threads().min(items.len()).max(1);
+    let best = AtomicUsize::new(usize::MAX);
+    let found = Mutex::new(None);
+    let (f, best_ref, found_ref) = (&f, &best, &found);
+
+    thread::scope(|scope| {
+        for first in 0..threads {
+            scope.spawn(move || {
+                for index in (first..items.len()).step_by(threads) {
+                    if index > best_ref.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    if let Some(result) = f(&items[index]) {
+                        let mut found = found_ref.lock().unwrap();
+
+                        if index < best_ref.load(Ordering::Relaxed) {
+                            best_ref.store(index, Ordering::Relaxed);
+                            *found = Some(result);
+                        }
+
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap()
+}
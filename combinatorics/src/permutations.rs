@@ -0,0 +1,97 @@
+/// Every ordering of some items, using Heap's algorithm. Each permutation
+/// is one swap away from the last, so there's one `Vec` per item yielded and
+/// nothing else.
+#[derive(Debug, Clone)]
+pub struct Permutations<T> {
+    items: Vec<T>,
+
+    /// Heap's algorithm's stack, unrolled into a counter per position.
+    counters: Vec<usize>,
+    position: usize,
+    started: bool,
+}
+
+impl<T: Clone> Permutations<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Permutations {
+            counters: vec![0; items.len()],
+            items,
+            position: 1,
+            started: false,
+        }
+    }
+
+    /// Swaps the items into the next ordering. Returns false once every
+    /// ordering has been seen.
+    fn advance(&mut self) -> bool {
+        while self.position < self.items.len() {
+            let position = self.position;
+            let counter = self.counters[position];
+
+            if counter < position {
+                if position.is_multiple_of(2) {
+                    self.items.swap(0, position);
+                } else {
+                    self.items.swap(counter, position);
+                }
+
+                self.counters[position] += 1;
+                self.position = 1;
+                return true;
+            }
+
+            self.counters[position] = 0;
+            self.position += 1;
+        }
+
+        false
+    }
+}
+
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if !self.started {
+            self.started = true;
+            return Some(self.items.clone());
+        }
+
+        if self.advance() {
+            Some(self.items.clone())
+        } else {
+            None
+        }
+    }
+}
+
+pub fn permutations<T: Clone>(items: &[T]) -> Permutations<T> {
+    Permutations::new(items.to_vec())
+}
+
+/// Calls `visit` with every ordering of `items`, rearranging them in place
+/// so that the only allocation is the counters. `items` is left in whatever
+/// ordering came last.
+pub fn permute<T>(items: &mut [T], mut visit: impl FnMut(&[T])) {
+    let mut counters = vec![0; items.len()];
+    let mut position = 1;
+
+    visit(items);
+
+    while position < items.len() {
+        if counters[position] < position {
+            if position.is_multiple_of(2) {
+                items.swap(0, position);
+            } else {
+                items.swap(counters[position], position);
+            }
+
+            counters[position] += 1;
+            position = 1;
+            visit(items);
+        } else {
+            counters[position] = 0;
+            position += 1;
+        }
+    }
+}
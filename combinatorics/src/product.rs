@@ -0,0 +1,50 @@
+/// Every pair of an item from `A` and an item from `B`, going through all of
+/// `B` for each item of `A`. `B` is cloned to start it over, so it should be
+/// cheap to clone, like a range.
+#[derive(Debug, Clone)]
+pub struct Product<A: Iterator, B> {
+    outer: A,
+    inner_start: B,
+    inner: B,
+    current: Option<A::Item>,
+}
+
+impl<A, B> Iterator for Product<A, B>
+where
+    A: Iterator,
+    A::Item: Clone,
+    B: Iterator + Clone,
+{
+    type Item = (A::Item, B::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.current.is_none() {
+                self.current = Some(self.outer.next()?);
+                self.inner = self.inner_start.clone();
+            }
+
+            match self.inner.next() {
+                Some(inner) => return Some((self.current.clone().unwrap(), inner)),
+                None => self.current = None,
+            }
+        }
+    }
+}
+
+pub fn product<A, B>(outer: A, inner: B) -> Product<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    A::Item: Clone,
+    B: IntoIterator,
+    B::IntoIter: Clone,
+{
+    let inner = inner.into_iter();
+
+    Product {
+        outer: outer.into_iter(),
+        inner_start: inner.clone(),
+        inner,
+        current: None,
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+
+use combinatorics::{
+    combinations, parallel_find_map, parallel_map, parallel_map_with, permutations, permute,
+    product,
+};
+
+#[test]
+fn permutations_are_complete() {
+    let all: Vec<Vec<u8>> = permutations(&[0, 1, 2, 3, 4]).collect();
+    let unique: HashSet<_> = all.iter().cloned().collect();
+
+    assert_eq!(all.len(), 120);
+    assert_eq!(unique.len(), 120);
+    assert_eq!(all[0], vec![0, 1, 2, 3, 4]);
+
+    assert_eq!(permutations::<u8>(&[]).count(), 1);
+    assert_eq!(permutations(&['a']).collect::<Vec<_>>(), vec![vec!['a']]);
+}
+
+#[test]
+fn permute_matches_iterator() {
+    let mut visited = Vec::new();
+    permute(&mut [1, 2, 3, 4], |order| visited.push(order.to_vec()));
+
+    assert_eq!(visited, permutations(&[1, 2, 3, 4]).collect::<Vec<_>>());
+}
+
+#[test]
+fn combinations_in_order() {
+    let all: Vec<String> = combinations(&['a', 'b', 'c', 'd'], 2)
+        .map(|pick| pick.into_iter().collect())
+        .collect();
+
+    assert_eq!(all, vec!["ab", "ac", "ad", "bc", "bd", "cd"]);
+    assert_eq!(combinations(&[1, 2, 3], 0).count(), 1);
+    assert_eq!(combinations(&[1, 2, 3], 3).count(), 1);
+    assert_eq!(combinations(&[1, 2, 3], 4).count(), 0);
+    assert_eq!(combinations(&[0; 8], 4).count(), 70);
+}
+
+#[test]
+fn product_pairs() {
+    let pairs: Vec<_> = product(0..2, "xy".chars()).collect();
+
+    assert_eq!(pairs, vec![(0, 'x'), (0, 'y'), (1, 'x'), (1, 'y')]);
+    assert_eq!(product(0..100, 0..100).count(), 10_000);
+    assert_eq!(product(0..3, 0..0).count(), 0);
+}
+
+#[test]
+fn parallel_helpers() {
+    let items: Vec<u64> = (0..1000).collect();
+
+    assert_eq!(
+        parallel_map(&items, |n| n * n),
+        items.iter().map(|n| n * n).collect::<Vec<_>>()
+    );
+
+    // several items match, and the earliest one has to win
+    assert_eq!(
+        parallel_find_map(&items, |&n| if n % 97 == 96 { Some(n) } else { None }),
+        Some(96)
+    );
+    assert_eq!(
+        parallel_find_map(&items, |&n| if n > 5000 { Some(n) } else { None }),
+        None
+    );
+    assert_eq!(parallel_map(&[] as &[u8], |&n| n), Vec::<u8>::new());
+
+    // each thread counts only the items it saw itself
+    let seen = parallel_map_with(
+        &items,
+        || 0,
+        |count, _| {
+            *count += 1;
+            *count
+        },
+    );
+    assert_eq!(seen.len(), items.len());
+    assert_eq!(seen[0], 1);
+    assert!(seen.iter().all(|&count| count <= items.len()));
+}
@@ -0,0 +1,371 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use crate::{
+    parser::{Expr, Function, Statement},
+    Error,
+};
+
+/// Stands in for the frame size in a function's code until the whole
+/// function has been generated and its size is known.
+const FRAME: &str = "{frame}";
+
+/// Turns parsed functions into assembly for `intcode::asm`, following its
+/// calling convention.
+///
+/// Every variable and intermediate value gets its own cell in the function's
+/// frame. Cells are handed out in order and given back at the end of each
+/// statement and block, so the frame is as big as the most cells in use at
+/// once.
+pub fn generate(functions: &[Function]) -> Result<String, Error> {
+    let mut arities = HashMap::new();
+
+    for function in functions {
+        if is_builtin(&function.name) {
+            return Err(Error::Compile {
+                line: function.line,
+                message: format!("{} is a builtin", function.name),
+            });
+        }
+
+        if arities
+            .insert(function.name.as_str(), function.params.len())
+            .is_some()
+        {
+            return Err(Error::Compile {
+                line: function.line,
+                message: format!("{} is defined twice", function.name),
+            });
+        }
+    }
+
+    match arities.get("main") {
+        Some(0) => {}
+        Some(_) => {
+            return Err(Error::Compile {
+                line: 1,
+                message: "main can't take arguments".to_owned(),
+            })
+        }
+        None => {
+            return Err(Error::Compile {
+                line: 1,
+                message: "no main function".to_owned(),
+            })
+        }
+    }
+
+    let mut externals = BTreeSet::new();
+    let mut code = String::new();
+
+    for function in functions {
+        let mut generator = Generator {
+            arities: &arities,
+            externals: &mut externals,
+            lines: Vec::new(),
+            scopes: vec![HashMap::new()],
+            top: 1,
+            frame: 1,
+            labels: 0,
+        };
+
+        code.push_str(&generator.function(function)?);
+    }
+
+    let mut header = String::from(".export main\n");
+    for external in externals {
+        writeln!(header, ".import {}", external).unwrap();
+    }
+
+    Ok(header + &code)
+}
+
+fn is_builtin(name: &str) -> bool {
+    name == "input" || name == "output"
+}
+
+fn slot(index: usize) -> String {
+    format!("[rb+{}]", index)
+}
+
+struct Generator<'a> {
+    arities: &'a HashMap<&'a str, usize>,
+    externals: &'a mut BTreeSet<String>,
+    lines: Vec<String>,
+
+    /// Variables in scope, innermost last, mapped to their cell.
+    scopes: Vec<HashMap<String, usize>>,
+
+    /// The next free cell in the frame.
+    top: usize,
+
+    /// How many cells the frame needs so far.
+    frame: usize,
+
+    labels: usize,
+}
+
+impl Generator<'_> {
+    fn emit(&mut self, line: String) {
+        self.lines.push(format!("    {}", line));
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!(".L{}", self.labels)
+    }
+
+    fn place(&mut self, label: &str) {
+        self.lines.push(format!("{}:", label));
+    }
+
+    fn allocate(&mut self) -> usize {
+        let cell = self.top;
+        self.top += 1;
+        self.frame = self.frame.max(self.top);
+        cell
+    }
+
+    fn function(&mut self, function: &Function) -> Result<String, Error> {
+        for param in &function.params {
+            let cell = self.allocate();
+            self.scopes[0].insert(param.clone(), cell);
+        }
+
+        self.block(&function.body)?;
+
+        // falling off the end returns 0
+        self.emit("mov 0, [rb+1]".to_owned());
+        self.emit("ret".to_owned());
+
+        let mut code = format!("\n{}:\n", function.name);
+        for line in &self.lines {
+            code.push_str(&line.replace(FRAME, &self.frame.to_string()));
+            code.push('\n');
+        }
+
+        Ok(code)
+    }
+
+    fn block(&mut self, statements: &[Statement]) -> Result<(), Error> {
+        let top = self.top;
+        self.scopes.push(HashMap::new());
+
+        for statement in statements {
+            self.statement(statement)?;
+        }
+
+        self.scopes.pop();
+        self.top = top;
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        let top = self.top;
+
+        match statement {
+            Statement::Let(name, value) => {
+                let cell = self.allocate();
+                let value = self.expr(value)?;
+                self.emit(format!("mov {}, {}", value, slot(cell)));
+
+                self.scopes.last_mut().unwrap().insert(name.clone(), cell);
+                self.top = cell + 1;
+                return Ok(());
+            }
+            Statement::Assign(name, line, value) => {
+                let cell = self.variable(name, *line)?;
+                let value = self.expr(value)?;
+                self.emit(format!("mov {}, {}", value, slot(cell)));
+            }
+            Statement::If(condition, then, otherwise) => {
+                let skip = self.label();
+                let end = self.label();
+
+                let condition = self.expr(condition)?;
+                self.emit(format!("jif {}, {}", condition, skip));
+                self.top = top;
+
+                self.block(then)?;
+                self.emit(format!("jmp {}", end));
+                self.place(&skip);
+                self.block(otherwise)?;
+                self.place(&end);
+            }
+            Statement::While(condition, body) => {
+                let start = self.label();
+                let end = self.label();
+
+                self.place(&start);
+                let condition = self.expr(condition)?;
+                self.emit(format!("jif {}, {}", condition, end));
+                self.top = top;
+
+                self.block(body)?;
+                self.emit(format!("jmp {}", start));
+                self.place(&end);
+            }
+            Statement::Return(value) => {
+                let value = match value {
+                    Some(value) => self.expr(value)?,
+                    None => "0".to_owned(),
+                };
+
+                self.emit(format!("mov {}, [rb+1]", value));
+                self.emit("ret".to_owned());
+            }
+            Statement::Expr(expr) => {
+                self.expr(expr)?;
+            }
+        }
+
+        self.top = top;
+        Ok(())
+    }
+
+    fn variable(&self, name: &str, line: usize) -> Result<usize, Error> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .ok_or_else(|| Error::Compile {
+                line,
+                message: format!("{} isn't defined", name),
+            })
+    }
+
+    /// Generates code for an expression, returning an operand that holds its
+    /// value afterwards.
+    fn expr(&mut self, expr: &Expr) -> Result<String, Error> {
+        let operand = match expr {
+            Expr::Number(value) => value.to_string(),
+            Expr::Variable(name, line) => slot(self.variable(name, *line)?),
+            Expr::Call(name, args, line) => return self.call(name, args, *line),
+            Expr::Negate(value) => {
+                let value = self.expr(value)?;
+                let result = slot(self.allocate());
+                self.emit(format!("mul {}, -1, {}", value, result));
+                result
+            }
+            Expr::Not(value) => {
+                let value = self.expr(value)?;
+                let result = slot(self.allocate());
+                self.emit(format!("cme {}, 0, {}", value, result));
+                result
+            }
+            Expr::Binary(op @ "&&", left, right) | Expr::Binary(op @ "||", left, right) => {
+                self.short_circuit(op, left, right)?
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                let result = slot(self.allocate());
+
+                match *op {
+                    "+" => self.emit(format!("add {}, {}, {}", left, right, result)),
+                    "*" => self.emit(format!("mul {}, {}, {}", left, right, result)),
+                    "-" => {
+                        self.emit(format!("mul {}, -1, {}", right, result));
+                        self.emit(format!("add {}, {}, {}", left, result, result));
+                    }
+                    "<" => self.emit(format!("cml {}, {}, {}", left, right, result)),
+                    ">" => self.emit(format!("cml {}, {}, {}", right, left, result)),
+                    "==" => self.emit(format!("cme {}, {}, {}", left, right, result)),
+
+                    // the rest are the negation of one of the above
+                    "<=" => self.emit(format!("cml {}, {}, {}", right, left, result)),
+                    ">=" => self.emit(format!("cml {}, {}, {}", left, right, result)),
+                    "!=" => self.emit(format!("cme {}, {}, {}", left, right, result)),
+                    _ => unreachable!("unknown operator {}", op),
+                }
+
+                if let "<=" | ">=" | "!=" = *op {
+                    self.emit(format!("cme {}, 0, {}", result, result));
+                }
+
+                result
+            }
+        };
+
+        Ok(operand)
+    }
+
+    /// `&&` and `||` only evaluate their right side when the left side
+    /// doesn't already decide the answer. Either way the result is 0 or 1.
+    fn short_circuit(&mut self, op: &str, left: &Expr, right: &Expr) -> Result<String, Error> {
+        let end = self.label();
+        let result = slot(self.allocate());
+
+        let left = self.expr(left)?;
+        if op == "&&" {
+            self.emit(format!("mov 0, {}", result));
+            self.emit(format!("jif {}, {}", left, end));
+        } else {
+            self.emit(format!("mov 1, {}", result));
+            self.emit(format!("jit {}, {}", left, end));
+        }
+
+        let right = self.expr(right)?;
+        self.emit(format!("cme {}, 0, {}", right, result));
+        self.emit(format!("cme {}, 0, {}", result, result));
+        self.place(&end);
+
+        Ok(result)
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr], line: usize) -> Result<String, Error> {
+        let expected = match name {
+            "input" => Some(0),
+            "output" => Some(1),
+            _ => self.arities.get(name).copied(),
+        };
+
+        if let Some(expected) = expected {
+            if expected != args.len() {
+                return Err(Error::Compile {
+                    line,
+                    message: format!(
+                        "{} takes {} arguments, found {}",
+                        name,
+                        expected,
+                        args.len()
+                    ),
+                });
+            }
+        } else {
+            self.externals.insert(name.to_owned());
+        }
+
+        // arguments are all evaluated before any are stored, since a call
+        // inside one of them would overwrite the argument cells
+        let args = args
+            .iter()
+            .map(|arg| self.expr(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match name {
+            "input" => {
+                let result = slot(self.allocate());
+                self.emit(format!("inn {}", result));
+                return Ok(result);
+            }
+            "output" => {
+                self.emit(format!("out {}", args[0]));
+                return Ok("0".to_owned());
+            }
+            _ => {}
+        }
+
+        for (index, arg) in args.iter().enumerate() {
+            self.emit(format!("mov {}, [rb+{}+{}]", arg, FRAME, index + 1));
+        }
+
+        self.emit(format!("call {}, {}", name, FRAME));
+
+        let result = slot(self.allocate());
+        self.emit(format!("mov [rb+{}+1], {}", FRAME, result));
+        Ok(result)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Number(i64),
+    Ident(String),
+    Punct(&'static str),
+}
+
+// longest first, so that `<=` isn't read as `<` then `=`
+static PUNCTUATION: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "<", ">", "=", "!", "(", ")", "{", "}", ",",
+    ";",
+];
+
+/// Splits source into tokens, each paired with the line it came from.
+pub fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let mut tokens = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = match line.find("//") {
+            Some(start) => &line[..start],
+            None => line,
+        };
+
+        let mut rest = line.trim_start();
+
+        while !rest.is_empty() {
+            let first = rest.chars().next().unwrap();
+
+            let len = if first.is_ascii_digit() {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+
+                let value = rest[..len].parse().map_err(|_| Error::Compile {
+                    line: line_number,
+                    message: format!("invalid number {}", &rest[..len]),
+                })?;
+
+                tokens.push((Token::Number(value), line_number));
+                len
+            } else if first.is_ascii_alphabetic() || first == '_' {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+
+                tokens.push((Token::Ident(rest[..len].to_owned()), line_number));
+                len
+            } else {
+                let punct = PUNCTUATION
+                    .iter()
+                    .find(|punct| rest.starts_with(*punct))
+                    .ok_or_else(|| Error::Compile {
+                        line: line_number,
+                        message: format!("unexpected character {:?}", first),
+                    })?;
+
+                tokens.push((Token::Punct(punct), line_number));
+                punct.len()
+            };
+
+            rest = rest[len..].trim_start();
+        }
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,71 @@
+//! A compiler for a small language that runs on the Intcode VM.
+//!
+//! ```text
+//! fn main() {
+//!     let n = input();
+//!     print_number(fib(n));
+//! }
+//!
+//! fn fib(n) {
+//!     if n < 2 {
+//!         return n;
+//!     }
+//!
+//!     return fib(n - 1) + fib(n - 2);
+//! }
+//! ```
+//!
+//! Every value is an integer. There are `let` bindings, assignment, `if` and
+//! `else`, `while`, and functions that can recurse. Operators are `+`, `-`,
+//! `*`, comparisons, `!`, and short-circuiting `&&` and `||`. There's no
+//! division, because Intcode has none.
+//!
+//! `input()` reads a value and `output(value)` writes one. Calling a function
+//! that isn't defined in the program links it from the standard library in
+//! `intcode::link`, like `print_number`.
+
+mod codegen;
+mod lexer;
+mod parser;
+
+use std::fmt;
+
+use intcode::{
+    asm::{self, AsmError},
+    link::{self, LinkError},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Compile { line: usize, message: String },
+    Assemble(AsmError),
+    Link(LinkError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Compile { line, message } => write!(formatter, "line {}: {}", line, message),
+            Error::Assemble(error) => write!(formatter, "generated bad assembly: {}", error),
+            Error::Link(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+/// Compiles a program to assembly for `intcode::asm`.
+pub fn compile(source: &str) -> Result<String, Error> {
+    let tokens = lexer::tokenize(source)?;
+    let functions = parser::parse(tokens)?;
+    codegen::generate(&functions)
+}
+
+/// Compiles a program and links it with the standard library, giving a
+/// program that's ready to load into a VM.
+pub fn build(source: &str) -> Result<Vec<i64>, Error> {
+    let assembly = compile(source)?;
+
+    let mut objects = link::stdlib();
+    objects.push(asm::assemble(&assembly).map_err(Error::Assemble)?);
+
+    link::link(&objects).map_err(Error::Link)
+}
@@ -0,0 +1,85 @@
+//! Compiles a program and prints the Intcode tape.
+//!
+//!     cargo run -p compiler -- program.txt [--asm] [--run] [--ascii]
+//!
+//! `--asm` prints the generated assembly instead. `--run` runs the program,
+//! reading numbers from stdin and printing each output on its own line.
+//! With `--ascii`, input and output are text instead.
+
+use std::{
+    fs,
+    io::{self, Read},
+    process,
+};
+
+use intcode::{IntcodeMachine, Outcome, VM};
+
+fn run(program: &[i64], ascii: bool) {
+    let mut stdin = String::new();
+    io::stdin()
+        .read_to_string(&mut stdin)
+        .expect("couldn't read stdin");
+
+    let mut vm = VM::default();
+    vm.load(program);
+
+    if ascii {
+        stdin.bytes().for_each(|byte| vm.push_input(byte as i64));
+    } else {
+        for value in stdin.split_whitespace() {
+            let value = value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} isn't a number", value));
+
+            vm.push_input(value);
+        }
+    }
+
+    loop {
+        let outcome = vm.run();
+
+        for value in vm.drain_output() {
+            if ascii {
+                print!("{}", value as u8 as char);
+            } else {
+                println!("{}", value);
+            }
+        }
+
+        match outcome {
+            Outcome::Terminated => break,
+            Outcome::SentOutput | Outcome::Paused => {}
+            Outcome::NeedsInput => panic!("System starved for input"),
+            Outcome::Error(error) => panic!("VM faulted: {:?}", error),
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .expect("usage: compiler <program> [--asm] [--run] [--ascii]");
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+
+    let source = fs::read_to_string(path).expect("couldn't read program");
+
+    let result = if flag("--asm") {
+        compiler::compile(&source).map(|assembly| print!("{}", assembly))
+    } else {
+        compiler::build(&source).map(|program| {
+            if flag("--run") {
+                run(&program, flag("--ascii"));
+            } else {
+                let tape: Vec<String> = program.iter().map(|value| value.to_string()).collect();
+                println!("{}", tape.join(","));
+            }
+        })
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    }
+}
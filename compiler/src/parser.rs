@@ -0,0 +1,301 @@
+use crate::{lexer::Token, Error};
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Let(String, Expr),
+    Assign(String, usize, Expr),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    Return(Option<Expr>),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(i64),
+    Variable(String, usize),
+    Call(String, Vec<Expr>, usize),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+// binary operators from loosest to tightest binding
+static PRECEDENCE: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["==", "!=", "<", ">", "<=", ">="],
+    &["+", "-"],
+    &["*"],
+];
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+pub fn parse(tokens: Vec<(Token, usize)>) -> Result<Vec<Function>, Error> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+
+    let mut functions = Vec::new();
+    while parser.peek().is_some() {
+        functions.push(parser.function()?);
+    }
+
+    Ok(functions)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Compile {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(next)) if *next == punct) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", punct)))
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(ident)) if ident == keyword => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        match self.next()? {
+            Token::Ident(ident) if !is_keyword(&ident) => Ok(ident),
+            _ => {
+                self.position -= 1;
+                Err(self.error("expected a name"))
+            }
+        }
+    }
+
+    fn function(&mut self) -> Result<Function, Error> {
+        let line = self.line();
+
+        if !self.eat_keyword("fn") {
+            return Err(self.error("expected fn"));
+        }
+
+        let name = self.ident()?;
+        self.expect("(")?;
+
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            if !params.is_empty() {
+                self.expect(",")?;
+            }
+
+            params.push(self.ident()?);
+        }
+
+        let body = self.block()?;
+
+        Ok(Function {
+            name,
+            params,
+            body,
+            line,
+        })
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, Error> {
+        self.expect("{")?;
+
+        let mut statements = Vec::new();
+        while !self.eat("}") {
+            statements.push(self.statement()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, Error> {
+        if self.eat_keyword("let") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            let value = self.expr()?;
+            self.expect(";")?;
+            return Ok(Statement::Let(name, value));
+        }
+
+        if self.eat_keyword("if") {
+            return self.if_statement();
+        }
+
+        if self.eat_keyword("while") {
+            let condition = self.expr()?;
+            let body = self.block()?;
+            return Ok(Statement::While(condition, body));
+        }
+
+        if self.eat_keyword("return") {
+            let value = if self.eat(";") {
+                None
+            } else {
+                let value = self.expr()?;
+                self.expect(";")?;
+                Some(value)
+            };
+
+            return Ok(Statement::Return(value));
+        }
+
+        let expr = self.expr()?;
+
+        if self.eat("=") {
+            let (name, line) = match expr {
+                Expr::Variable(name, line) => (name, line),
+                _ => return Err(self.error("can only assign to variables")),
+            };
+
+            let value = self.expr()?;
+            self.expect(";")?;
+            return Ok(Statement::Assign(name, line, value));
+        }
+
+        self.expect(";")?;
+        Ok(Statement::Expr(expr))
+    }
+
+    fn if_statement(&mut self) -> Result<Statement, Error> {
+        let condition = self.expr()?;
+        let then = self.block()?;
+
+        let otherwise = if !self.eat_keyword("else") {
+            Vec::new()
+        } else if self.eat_keyword("if") {
+            vec![self.if_statement()?]
+        } else {
+            self.block()?
+        };
+
+        Ok(Statement::If(condition, then, otherwise))
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, Error> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+
+        'operators: loop {
+            for &op in PRECEDENCE[level] {
+                if self.eat(op) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(op, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let line = self.line();
+
+        match self.next()? {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) if !is_keyword(&name) => {
+                if !self.eat("(") {
+                    return Ok(Expr::Variable(name, line));
+                }
+
+                let mut args = Vec::new();
+                while !self.eat(")") {
+                    if !args.is_empty() {
+                        self.expect(",")?;
+                    }
+
+                    args.push(self.expr()?);
+                }
+
+                Ok(Expr::Call(name, args, line))
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("expected an expression"))
+            }
+        }
+    }
+}
+
+fn is_keyword(ident: &str) -> bool {
+    matches!(ident, "fn" | "let" | "if" | "else" | "while" | "return")
+}
@@ -0,0 +1,190 @@
+use compiler::{build, Error};
+use intcode::{link::LinkError, CachedVM, IntcodeMachine, VM};
+
+fn run(source: &str, input: &[i64]) -> Vec<i64> {
+    let program = build(source).unwrap_or_else(|error| panic!("{}", error));
+
+    let mut vm = VM::default();
+    vm.load(&program);
+    input.iter().for_each(|&value| vm.push_input(value));
+    let output = vm.run_to_end();
+
+    // the generated code doesn't modify itself, but the stdlib does, so
+    // check the cached backend keeps up
+    let mut cached = CachedVM::new(&program);
+    input.iter().for_each(|&value| cached.push_input(value));
+    assert_eq!(cached.run_to_end(), output);
+
+    output
+}
+
+fn compile_error(source: &str) -> (usize, String) {
+    match build(source) {
+        Err(Error::Compile { line, message }) => (line, message),
+        other => panic!("expected a compile error, got {:?}", other),
+    }
+}
+
+#[test]
+fn recursion() {
+    let source = "
+        fn main() {
+            output(fib(input()));
+        }
+
+        fn fib(n) {
+            if n < 2 {
+                return n;
+            }
+
+            return fib(n - 1) + fib(n - 2);
+        }
+    ";
+
+    assert_eq!(run(source, &[20]), vec![6765]);
+}
+
+#[test]
+fn loops_and_variables() {
+    let source = "
+        fn main() {
+            let n = input();
+            let total = 1;
+
+            while n > 1 {
+                total = total * n;
+                n = n - 1;
+            }
+
+            output(total);
+        }
+    ";
+
+    assert_eq!(run(source, &[10]), vec![3628800]);
+}
+
+#[test]
+fn operators() {
+    let source = "
+        fn main() {
+            let a = input();
+            let b = input();
+
+            output(a + b);
+            output(a - b);
+            output(a * b);
+            output(-a);
+            output(a < b);
+            output(a > b);
+            output(a <= b);
+            output(a >= b);
+            output(a == b);
+            output(a != b);
+            output(!a);
+            output(1 + 2 * 3 - 4);
+            output((1 + 2) * 3);
+        }
+    ";
+
+    assert_eq!(
+        run(source, &[3, 5]),
+        vec![8, -2, 15, -3, 1, 0, 1, 0, 0, 1, 0, 3, 9]
+    );
+}
+
+#[test]
+fn short_circuit() {
+    let source = "
+        fn main() {
+            output(0 && noisy(1));
+            output(2 && noisy(3));
+            output(4 || noisy(5));
+            output(0 || noisy(0));
+        }
+
+        fn noisy(value) {
+            output(100 + value);
+            return value;
+        }
+    ";
+
+    assert_eq!(run(source, &[]), vec![0, 103, 1, 1, 100, 0]);
+}
+
+#[test]
+fn scopes_and_else_if() {
+    let source = "
+        fn main() {
+            let x = 1;
+
+            if x == 0 {
+                output(10);
+            } else if x == 1 {
+                let x = 20;
+                output(x);
+            } else {
+                output(30);
+            }
+
+            output(x);
+        }
+    ";
+
+    assert_eq!(run(source, &[]), vec![20, 1]);
+}
+
+#[test]
+fn nested_calls() {
+    let source = "
+        fn main() {
+            output(add(add(1, 2), add(3, mul_loop(4, 5))));
+        }
+
+        fn add(a, b) {
+            return a + b;
+        }
+    ";
+
+    assert_eq!(run(source, &[]), vec![26]);
+}
+
+#[test]
+fn stdlib() {
+    let source = "
+        fn main() {
+            print_number(-1234);
+        }
+    ";
+
+    assert_eq!(
+        run(source, &[]),
+        "-1234".bytes().map(i64::from).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        compile_error("fn main() {\n    output(x);\n}"),
+        (2, "x isn't defined".to_owned())
+    );
+
+    assert_eq!(
+        compile_error("fn main() {\n    f(1);\n}\nfn f(a, b) {}"),
+        (2, "f takes 2 arguments, found 1".to_owned())
+    );
+
+    assert_eq!(
+        compile_error("fn main() {\n    let = 1;\n}"),
+        (2, "expected a name".to_owned())
+    );
+
+    assert_eq!(compile_error("fn other() {}").1, "no main function");
+
+    assert_eq!(
+        build("fn main() { missing(); }"),
+        Err(Error::Link(LinkError::UndefinedSymbol {
+            name: "missing".to_owned()
+        }))
+    );
+}
@@ -0,0 +1,151 @@
+//! Runs day 2's gravity assist tapes and solves for the noun and verb that
+//! give an output, kept as a library so the solver can be tested.
+
+use std::fmt;
+
+use combinatorics::{parallel_find_map, product};
+use intcode::{Error, IntcodeMachine, Outcome, VM};
+
+/// How many instructions a tape can run before it's assumed to loop
+/// forever. The puzzle's tapes finish in a few dozen.
+pub const STEP_LIMIT: u64 = 100_000;
+
+/// Day 2 tapes don't do input or output, so a tape that asks for input
+/// will never get any.
+#[derive(Debug)]
+pub enum RunError {
+    Fault(Error),
+    Starved,
+
+    /// The tape ran `STEP_LIMIT` instructions without halting.
+    Runaway,
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Fault(error) => write!(formatter, "faulted: {:?}", error),
+            RunError::Starved => write!(formatter, "asked for input"),
+            RunError::Runaway => write!(formatter, "ran {} steps without halting", STEP_LIMIT),
+        }
+    }
+}
+
+/// Makes a VM for a day 2 tape with its noun and verb set. These programs
+/// only ever touch their own tape, so storing past the end of it is a
+/// fault rather than a reason to grow memory, and they're cut off after
+/// `STEP_LIMIT` instructions.
+pub fn vm(tape: &[i64], noun: i64, verb: i64) -> VM<i64> {
+    let mut vm = VM::default();
+    vm.load_memory(tape);
+    vm.memory_limit = Some(tape.len());
+    vm.set_fuel(Some(STEP_LIMIT));
+    vm.memory[1] = noun;
+    vm.memory[2] = verb;
+    vm
+}
+
+pub fn run(vm: &mut VM<i64>) -> Result<(), RunError> {
+    loop {
+        match vm.run_partial() {
+            Outcome::Terminated => return Ok(()),
+            Outcome::SentOutput => {}
+            Outcome::Paused => return Err(RunError::Runaway),
+            Outcome::NeedsInput => return Err(RunError::Starved),
+            Outcome::Error(error) => return Err(RunError::Fault(error)),
+        }
+    }
+}
+
+/// Runs a fresh copy of the program with a noun and verb and returns what
+/// ends up at address 0.
+pub fn output(tape: &[i64], noun: i64, verb: i64) -> Result<i64, RunError> {
+    let mut vm = vm(tape, noun, verb);
+    run(&mut vm)?;
+    Ok(vm.memory[0])
+}
+
+/// Points to check the program against before trusting it to be affine.
+/// The corners catch anything that only bends near the edges.
+static PROBES: &[(i64, i64)] = &[(1, 1), (99, 0), (0, 99), (99, 99), (37, 58), (62, 13)];
+
+/// The output as `constant + noun * a + verb * b`, which is what programs
+/// that only add and multiply the noun and verb by constants look like.
+pub struct Affine {
+    pub constant: i64,
+    pub noun: i64,
+    pub verb: i64,
+}
+
+impl Affine {
+    /// Measures the program at the origin and one step along each input,
+    /// then checks that guess against the rest of `PROBES`.
+    /// Programs that fault or overflow anywhere along the way aren't affine
+    /// either.
+    pub fn probe(tape: &[i64]) -> Option<Affine> {
+        let at = |noun, verb| output(tape, noun, verb).ok();
+        let constant = at(0, 0)?;
+
+        let affine = Affine {
+            constant,
+            noun: at(1, 0)?.checked_sub(constant)?,
+            verb: at(0, 1)?.checked_sub(constant)?,
+        };
+
+        PROBES
+            .iter()
+            .all(|&(noun, verb)| {
+                let expected = affine.at(noun, verb);
+                expected.is_some() && at(noun, verb) == expected
+            })
+            .then_some(affine)
+    }
+
+    /// The output for a noun and verb, or `None` if working it out
+    /// overflows.
+    pub fn at(&self, noun: i64, verb: i64) -> Option<i64> {
+        self.noun
+            .checked_mul(noun)?
+            .checked_add(self.verb.checked_mul(verb)?)?
+            .checked_add(self.constant)
+    }
+
+    /// Tries each noun and works out which verb would hit `goal`, without
+    /// running the program at all.
+    pub fn solve(&self, goal: i64) -> Option<(i64, i64)> {
+        (0..100).find_map(|noun| {
+            let rest = goal.checked_sub(self.at(noun, 0)?)?;
+
+            let verb = match self.verb {
+                0 if rest == 0 => 0,
+                0 => return None,
+                step if rest.checked_rem(step) == Some(0) => rest.checked_div(step)?,
+                _ => return None,
+            };
+
+            (0..100).contains(&verb).then_some((noun, verb))
+        })
+    }
+}
+
+/// Solves for `goal` without a search if the program is affine. The affine
+/// guess is only as good as the probes, so its answer is checked with a real
+/// run before it's believed.
+pub fn solve_affine(tape: &[i64], goal: i64) -> Option<(i64, i64)> {
+    Affine::probe(tape)
+        .and_then(|affine| affine.solve(goal))
+        .filter(|&(noun, verb)| output(tape, noun, verb).ok() == Some(goal))
+}
+
+/// Tries every noun and verb on every core, stopping once one works.
+pub fn search(tape: &[i64], goal: i64) -> Option<(i64, i64)> {
+    let pairs: Vec<(i64, i64)> = product(0..100, 0..100).collect();
+
+    parallel_find_map(&pairs, |&(verb, noun)| {
+        if output(tape, noun, verb).ok() == Some(goal) {
+            Some((noun, verb))
+        } else {
+            None
+        }
+    })
+}
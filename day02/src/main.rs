@@ -1,74 +1,46 @@
-static INPUT: &str = include_str!("../input.txt");
-
-const OP_ADD: usize = 1;
-const OP_MUL: usize = 2;
-const OP_END: usize = 99;
-
-fn run(memory: &mut [usize]) {
-    let mut cursor = 0;
-
-    loop {
-        match memory[cursor] {
-            OP_ADD => {
-                let p_a = memory[cursor + 1];
-                let p_b = memory[cursor + 2];
-                let p_out = memory[cursor + 3];
-
-                memory[p_out] = memory[p_a] + memory[p_b];
+use day02::{run, search, solve_affine, vm};
+use intcode::{diff, VM};
 
-                cursor += 4;
-            }
-            OP_MUL => {
-                let p_a = memory[cursor + 1];
-                let p_b = memory[cursor + 2];
-                let p_out = memory[cursor + 3];
-
-                memory[p_out] = memory[p_a] * memory[p_b];
-
-                cursor += 4;
-            }
-            OP_END => break,
-            _ => panic!("illegal instruction"),
-        }
-    }
-}
+static INPUT: &str = include_str!("../input.txt");
 
-fn load() -> Vec<usize> {
-    INPUT.split(',').map(|v| v.parse().unwrap()).collect()
+fn load() -> Vec<i64> {
+    VM::decode_tape(INPUT)
 }
 
-fn part_one() {
-    let mut memory = load();
+fn part_one(show_diff: bool) {
+    let before = vm(&load(), 12, 2);
+    let mut after = before.clone();
 
-    memory[1] = 12;
-    memory[2] = 2;
+    if let Err(error) = run(&mut after) {
+        panic!("Gravity assist {}", error);
+    }
 
-    run(&mut memory);
+    println!("Day one: {}", after.memory[0]);
 
-    println!("Day one: {}", memory[0]);
+    if show_diff {
+        print!("{}", diff::report_vms(&before, &after));
+    }
 }
 
 fn part_two() {
-    let base_memory = load();
+    let tape = load();
     let goal_output = 19690720;
 
-    for verb in 0..100 {
-        for noun in 0..100 {
-            let mut memory = base_memory.clone();
-            memory[1] = noun;
-            memory[2] = verb;
-
-            run(&mut memory);
-
-            if memory[0] == goal_output {
-                println!("Noun: {}, verb: {}", noun, verb);
-                println!("Day two: {}", 100 * noun + verb);
-            }
+    let (noun, verb) = match solve_affine(&tape, goal_output) {
+        Some(answer) => answer,
+        None => {
+            println!("Couldn't solve for the goal directly, searching instead");
+            search(&tape, goal_output).expect("No noun and verb give the goal")
         }
-    }
+    };
+
+    println!("Noun: {}, verb: {}", noun, verb);
+    println!("Day two: {}", 100 * noun + verb);
 }
 
 fn main() {
-    part_one();
+    let show_diff = std::env::args().any(|arg| arg == "--diff");
+
+    part_one(show_diff);
     part_two();
 }
@@ -0,0 +1,57 @@
+use day02::{output, search, solve_affine, Affine};
+
+/// Stores 7 + 3 * noun + 5 * verb at address 0. The first instruction reads
+/// the noun and verb as immediates, so they're never used as addresses.
+static AFFINE: &[i64] = &[
+    1101, 0, 0, 3, //
+    1002, 1, 3, 0, //
+    1002, 2, 5, 3, //
+    1, 0, 3, 0, //
+    1001, 0, 7, 0, //
+    99,
+];
+
+/// Stores noun * verb at address 0.
+static PRODUCT: &[i64] = &[1102, 0, 0, 0, 99];
+
+#[test]
+fn detects_affine_tape() {
+    let affine = Affine::probe(AFFINE).unwrap();
+
+    assert_eq!((affine.constant, affine.noun, affine.verb), (7, 3, 5));
+    assert_eq!(affine.at(12, 34), Some(213));
+    assert!(Affine::probe(PRODUCT).is_none());
+}
+
+#[test]
+fn solves_affine_tape() {
+    let (noun, verb) = solve_affine(AFFINE, 213).unwrap();
+
+    assert_eq!(output(AFFINE, noun, verb).ok(), Some(213));
+
+    // 7 + 3 * noun + 5 * verb only ever lands on some goals
+    assert_eq!(solve_affine(AFFINE, 6), None);
+}
+
+#[test]
+fn falls_back_to_search() {
+    assert_eq!(solve_affine(PRODUCT, 408), None);
+
+    let (noun, verb) = search(PRODUCT, 408).unwrap();
+    assert_eq!(noun * verb, 408);
+}
+
+#[test]
+fn overflow_isnt_affine() {
+    let huge = Affine {
+        constant: i64::MAX,
+        noun: 1,
+        verb: i64::MIN,
+    };
+
+    assert_eq!(huge.at(0, 0), Some(i64::MAX));
+    assert_eq!(huge.at(1, 0), None);
+    assert_eq!(huge.at(0, 2), None);
+    assert_eq!(huge.solve(0), None);
+    assert_eq!(huge.solve(i64::MIN), None);
+}
@@ -0,0 +1,9 @@
+use day02::{output, RunError};
+
+#[test]
+fn endless_tape_runs_away() {
+    // jmp 0 forever, reading the noun and verb as immediates
+    let tape = [1105, 1, 0, 99];
+
+    assert!(matches!(output(&tape, 1, 0), Err(RunError::Runaway)));
+}
@@ -49,8 +49,8 @@ fn main() {
         num_steps += 1;
     });
 
-    let mut closest_dist = i32::max_value();
-    let mut fewest_steps = u32::max_value();
+    let mut closest_dist = i32::MAX;
+    let mut fewest_steps = u32::MAX;
 
     let mut num_steps = 1;
     travel(&second_wire, |pos| {
@@ -0,0 +1,4 @@
+//! Day 5's interpreter, kept as a library so the fuzzer can run it as a
+//! reference backend next to the shared VM.
+
+pub mod intcode;
@@ -32,7 +32,7 @@ fn part_one() {
 
     let sum: usize = orbits
         .keys()
-        .map(|key| compute_cost(&orbits, &mut costs, *key))
+        .map(|key| compute_cost(&orbits, &mut costs, key))
         .sum();
 
     println!("Part one: {}", sum);
@@ -1,131 +1,184 @@
-use intcode::{Outcome, VM};
+use std::ops::Range;
 
-mod intcode;
+use combinatorics::{combinations, parallel_map_with, permutations};
+use intcode::{circuit::Circuit, QueryOracle, VM};
 
 static INPUT: &str = include_str!("../input.txt");
 
-fn get_thruster_signal(memory: &[i32], phases: &[u8]) -> i32 {
-    let mut output = 0;
-
-    for &phase in phases {
-        let mut vm = VM::default();
-        vm.load_memory(memory);
-        vm.put_input(phase as i32);
-        vm.put_input(output);
-        vm.run_until_terminated();
-
-        output = vm.get_output();
-    }
-
-    output
+struct Options {
+    parallel: bool,
+    amplifiers: usize,
+    phases: Range<u8>,
+    feedback_phases: Range<u8>,
+    circuit: Option<String>,
+    feedback_circuit: Option<String>,
 }
 
-fn get_thruster_signal_with_feedback(memory: &[i32], phases: &[u8]) -> i32 {
-    let mut vms: Vec<_> = phases
-        .iter()
-        .map(|&phase| {
-            let mut vm = VM::default();
-            vm.load_memory(memory);
-            vm.put_input(phase as i32);
-            vm
-        })
-        .collect();
-
-    let num_vms = vms.len();
-
-    let mut vm_index = 0;
-    let mut final_vm_output = 0;
-
-    let next_vm_index = |index| (index + 1) % num_vms;
-    let is_final_vm = |index| index == num_vms - 1;
-
-    vms[0].put_input(0);
-
-    loop {
-        let vm = &mut vms[vm_index];
-
-        match vm.run_partial() {
-            Outcome::Terminated => {
-                if is_final_vm(vm_index) {
-                    return final_vm_output;
-                }
+impl Options {
+    /// Reads `--parallel`, `--amplifiers N`, `--phases A..B`,
+    /// `--feedback-phases A..B`, `--circuit FILE` and
+    /// `--feedback-circuit FILE`, falling back to the puzzle's own setup.
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |name: &str| {
+            args.windows(2)
+                .find(|pair| pair[0] == name)
+                .map(|pair| pair[1].clone())
+        };
+
+        let range = |name: &str, default: Range<u8>| match value(name) {
+            Some(text) => {
+                let mut ends = text
+                    .split("..")
+                    .map(|end| end.parse().expect("Bad phase range"));
+                ends.next().unwrap()..ends.next().expect("Phase ranges look like 0..5")
             }
-            Outcome::NeedsInput => panic!("VM starved"),
-            Outcome::SentOutput => {
-                let output = vm.get_output();
+            None => default,
+        };
 
-                if is_final_vm(vm_index) {
-                    final_vm_output = output;
-                }
+        let circuit = value("--circuit");
+        let feedback_circuit = value("--feedback-circuit");
+        let amplifiers = value("--amplifiers");
 
-                let next_vm = &mut vms[next_vm_index(vm_index)];
-                next_vm.put_input(output);
-            }
+        // a circuit file says how many amplifiers it has
+        if amplifiers.is_some() && (circuit.is_some() || feedback_circuit.is_some()) {
+            panic!("--amplifiers can't be combined with --circuit or --feedback-circuit");
         }
 
-        vm_index = next_vm_index(vm_index);
+        Options {
+            parallel: args.iter().any(|arg| arg == "--parallel"),
+            amplifiers: amplifiers.map_or(5, |count| count.parse().expect("Bad amplifier count")),
+            phases: range("--phases", 0..5),
+            feedback_phases: range("--feedback-phases", 5..10),
+            circuit,
+            feedback_circuit,
+        }
     }
 }
 
-fn permutations(values: &[u8]) -> Vec<Vec<u8>> {
-    let mut perms = Vec::new();
-    permutations_inner(values, &mut |next: Vec<u8>| {
-        perms.push(next);
-    });
-    perms
+fn to_phases(phases: &[u8]) -> Vec<i32> {
+    phases.iter().map(|&phase| phase as i32).collect()
 }
 
-fn permutations_inner(values: &[u8], callback: &mut dyn FnMut(Vec<u8>)) {
-    match values.len() {
-        0 => {}
-        1 => callback(values.to_vec()),
-        _ => {
-            for i in 0..values.len() {
-                let value = values[i];
-                let others = &[&values[..i], &values[i + 1..]].concat();
-
-                permutations_inner(&others, &mut |mut buf: Vec<u8>| {
-                    buf.push(value);
-                    callback(buf);
-                });
-            }
+/// Runs `circuit` with one amplifier per phase and returns what comes out.
+fn get_thruster_signal(program: &[i32], circuit: &Circuit, phases: &[u8]) -> i32 {
+    circuit
+        .solve(program, &to_phases(phases))
+        .unwrap_or_else(|error| panic!("Amplifiers failed: {}", error))
+}
+
+/// Each amplifier in a chain is a pure function of its phase and input
+/// signal, and the same pairs come up over and over across settings, so
+/// they all go through one oracle.
+fn get_chain_signal(amplifier: &mut QueryOracle<VM<i32>>, circuit: &Circuit, phases: &[u8]) -> i32 {
+    circuit
+        .solve_chain(amplifier, &to_phases(phases))
+        .unwrap_or_else(|error| panic!("Amplifiers failed: {}", error))
+}
+
+/// Loads a circuit description from the file at `path`, or builds one with
+/// `default` and the number of amplifiers.
+fn load_circuit(
+    path: &Option<String>,
+    amplifiers: usize,
+    default: fn(usize) -> Circuit,
+) -> Circuit {
+    match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).expect("Couldn't read circuit");
+            Circuit::parse(&text).unwrap_or_else(|error| panic!("Bad circuit: {}", error))
         }
+        None => default(amplifiers),
     }
 }
 
-fn part_one() {
-    let program = VM::decode_tape(INPUT);
+/// Every way to give each amplifier a different phase from `phases`.
+fn phase_settings(phases: Range<u8>, amplifiers: usize) -> Vec<Vec<u8>> {
+    let phases: Vec<u8> = phases.collect();
 
-    let best = permutations(&[0, 1, 2, 3, 4])
-        .into_iter()
-        .map(|phases| {
-            let signal = get_thruster_signal(&program, &phases);
+    combinations(&phases, amplifiers)
+        .flat_map(|picked| permutations(&picked))
+        .collect()
+}
 
-            (phases, signal)
-        })
+/// Finds the phase setting with the strongest signal. Each thread gets its
+/// own state from `init` to evaluate settings with.
+fn best_setting<S>(
+    settings: &[Vec<u8>],
+    parallel: bool,
+    init: impl Fn() -> S + Sync,
+    evaluate: impl Fn(&mut S, &[u8]) -> i32 + Sync,
+) -> Option<(Vec<u8>, i32)> {
+    let signals = if parallel {
+        parallel_map_with(settings, init, |state, phases| evaluate(state, phases))
+    } else {
+        let mut state = init();
+        settings
+            .iter()
+            .map(|phases| evaluate(&mut state, phases))
+            .collect()
+    };
+
+    settings
+        .iter()
+        .cloned()
+        .zip(signals)
         .max_by_key(|(_phases, signal)| *signal)
-        .unwrap();
+}
 
-    println!("Part one: {} (permutations {:?})", best.1, best.0);
+/// Chains go through a per-thread oracle, and anything else is run as a
+/// whole circuit.
+fn strongest_signal(
+    program: &[i32],
+    circuit: &Circuit,
+    phases: Range<u8>,
+    parallel: bool,
+) -> (Vec<u8>, i32) {
+    let settings = phase_settings(phases, circuit.nodes.len());
+
+    let best = if circuit.chain_order().is_some() {
+        best_setting(
+            &settings,
+            parallel,
+            || QueryOracle::from_tape(program),
+            |amplifier, phases| get_chain_signal(amplifier, circuit, phases),
+        )
+    } else {
+        best_setting(
+            &settings,
+            parallel,
+            || (),
+            |_, phases| get_thruster_signal(program, circuit, phases),
+        )
+    };
+
+    best.expect("Not enough phases for every amplifier")
 }
 
-fn part_two() {
+fn part_one(options: &Options) {
     let program = VM::decode_tape(INPUT);
+    let circuit = load_circuit(&options.circuit, options.amplifiers, Circuit::chain);
+    let best = strongest_signal(&program, &circuit, options.phases.clone(), options.parallel);
 
-    let best = permutations(&[5, 6, 7, 8, 9])
-        .into_iter()
-        .map(|phases| {
-            let signal = get_thruster_signal_with_feedback(&program, &phases);
+    println!("Part one: {} (permutations {:?})", best.1, best.0);
+}
 
-            (phases, signal)
-        })
-        .max_by_key(|(_phases, signal)| *signal)
-        .unwrap();
+fn part_two(options: &Options) {
+    let program = VM::decode_tape(INPUT);
+    let circuit = load_circuit(&options.feedback_circuit, options.amplifiers, Circuit::ring);
+    let best = strongest_signal(
+        &program,
+        &circuit,
+        options.feedback_phases.clone(),
+        options.parallel,
+    );
 
     println!("Part two: {} (permutations {:?})", best.1, best.0);
 }
 
 fn main() {
-    part_one();
-    part_two();
+    let options = Options::from_args();
+
+    part_one(&options);
+    part_two(&options);
 }
@@ -32,7 +32,7 @@ fn show_image(size: (usize, usize), data: &[u8]) -> String {
             write!(output, "{}", char).unwrap();
         }
 
-        write!(output, "\n").unwrap();
+        writeln!(output).unwrap();
     }
 
     output
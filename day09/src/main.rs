@@ -1,28 +1,66 @@
-mod intcode;
+use intcode::{CachedVM, IntcodeMachine, VM};
 
 static INPUT: &str = include_str!("../input.txt");
 
-use intcode::VM;
+struct Options {
+    detect_self_modification: bool,
+    cached: bool,
+}
 
-fn part_one() {
-    let mut vm = VM::default();
-    vm.load_memory_from_tape(INPUT);
-    vm.put_input(1);
-    vm.run_until_terminated();
+fn report_self_modification(vm: &VM<i64>) {
+    if vm.self_modifications.is_empty() {
+        println!("No self-modifying writes detected");
+        return;
+    }
+
+    for modification in &vm.self_modifications {
+        println!(
+            "pc {} wrote {} to {} ({:?})",
+            modification.pc, modification.value, modification.target, modification.kind
+        );
+    }
+}
+
+fn run_boost<M: IntcodeMachine<Word = i64>>(vm: &mut M, mode: i64) -> i64 {
+    vm.load(&VM::decode_tape(INPUT));
+    vm.push_input(mode);
 
-    println!("Part one: {:?}", vm.get_output());
+    let output = vm.run_to_end();
+    *output.last().expect("BOOST didn't output anything")
 }
 
-fn part_two() {
+// self-modification detection needs hooks into the interpreter, which the
+// cached backend doesn't have
+fn boost(options: &Options, mode: i64) -> i64 {
+    if options.cached {
+        return run_boost(&mut CachedVM::default(), mode);
+    }
+
     let mut vm = VM::default();
-    vm.load_memory_from_tape(INPUT);
-    vm.put_input(2);
-    vm.run_until_terminated();
+    vm.detect_self_modification = options.detect_self_modification;
+    let output = run_boost(&mut vm, mode);
 
-    println!("Part two: {:?}", vm.get_output());
+    if options.detect_self_modification {
+        report_self_modification(&vm);
+    }
+
+    output
+}
+
+fn part_one(options: &Options) {
+    println!("Part one: {:?}", boost(options, 1));
+}
+
+fn part_two(options: &Options) {
+    println!("Part two: {:?}", boost(options, 2));
 }
 
 fn main() {
-    part_one();
-    part_two();
+    let options = Options {
+        detect_self_modification: std::env::args().any(|arg| arg == "--detect-smc"),
+        cached: std::env::args().any(|arg| arg == "--cached"),
+    };
+
+    part_one(&options);
+    part_two(&options);
 }
@@ -147,7 +147,7 @@ fn main() {
     //
     // so we sort the cream of the crop roids here
     for roids in best_sightlines.values_mut() {
-        roids.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+        roids.sort_unstable_by_key(|roid| std::cmp::Reverse(roid.1));
     }
 
     // spin around in a circle and fire our laser
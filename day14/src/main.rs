@@ -5,7 +5,7 @@ static INPUT: &str = include_str!("../input.txt");
 type FormulaeBook = BTreeMap<&'static str, (u32, Vec<(u32, &'static str)>)>;
 
 fn div_round_up(a: u32, b: u32) -> u32 {
-    (a + b - 1) / b
+    a.div_ceil(b)
 }
 
 fn parse_chemical(value: &str) -> (u32, &str) {
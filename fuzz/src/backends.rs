@@ -0,0 +1,178 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+};
+
+use day05::intcode::VM as Day05VM;
+use intcode::{BigInt, CachedVM, Fuel, IntcodeMachine, Outcome, Word, VM};
+
+/// Stores past this point are treated as a fault, so a random tape can't
+/// make an interpreter allocate gigabytes.
+const MEMORY_LIMIT: usize = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum End {
+    Halted,
+    NeedsInput,
+    OutOfFuel,
+    Fault(String),
+
+    /// The `intcode` machines report faults as errors, so one of them
+    /// panicking is a bug no matter what the other backends did.
+    Panicked(String),
+}
+
+impl End {
+    /// Faults are compared by kind only, since every interpreter words its
+    /// errors differently.
+    fn same_kind(&self, other: &End) -> bool {
+        match (self, other) {
+            (End::Panicked(_), _) | (_, End::Panicked(_)) => false,
+            (End::Fault(_), End::Fault(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub outputs: Vec<String>,
+    pub end: End,
+}
+
+impl Run {
+    pub fn agrees_with(&self, other: &Run) -> bool {
+        self.outputs == other.outputs && self.end.same_kind(&other.end)
+    }
+}
+
+pub struct Backend {
+    pub name: &'static str,
+    pub run: fn(&[i32], &[i32], u64) -> Run,
+}
+
+pub static BACKENDS: &[Backend] = &[
+    Backend {
+        name: "day05",
+        run: run_day05,
+    },
+    Backend {
+        name: "VM<i32>",
+        run: run_vm::<i32>,
+    },
+    Backend {
+        name: "VM<i64>",
+        run: run_vm::<i64>,
+    },
+    Backend {
+        name: "VM<i128>",
+        run: run_vm::<i128>,
+    },
+    Backend {
+        name: "VM<BigInt>",
+        run: run_vm::<BigInt>,
+    },
+    Backend {
+        name: "CachedVM<i64>",
+        run: run_cached::<i64>,
+    },
+];
+
+fn run_vm<W: Word + 'static>(tape: &[i32], inputs: &[i32], fuel: u64) -> Run {
+    let mut vm: VM<W> = VM::default();
+    vm.load_memory(tape.iter().map(|&value| W::from(value)).collect::<Vec<_>>());
+    vm.input = inputs.iter().map(|&value| W::from(value)).collect();
+    vm.memory_limit = Some(MEMORY_LIMIT);
+    vm.set_hook(Arc::new(Mutex::new(Fuel { remaining: fuel })));
+
+    run_machine(vm)
+}
+
+fn run_cached<W: Word + 'static>(tape: &[i32], inputs: &[i32], fuel: u64) -> Run {
+    let program: Vec<W> = tape.iter().map(|&value| W::from(value)).collect();
+    let mut vm = CachedVM::new(&program);
+    vm.input = inputs.iter().map(|&value| W::from(value)).collect();
+    vm.memory_limit = Some(MEMORY_LIMIT);
+    vm.fuel = Some(fuel);
+
+    run_machine(vm)
+}
+
+fn run_machine<M: IntcodeMachine>(mut vm: M) -> Run {
+    let mut outputs = Vec::new();
+
+    let end = loop {
+        match quietly(|| vm.run()) {
+            Ok(Outcome::SentOutput) => {
+                outputs.extend(vm.drain_output().iter().map(|value| value.to_string()))
+            }
+            Ok(Outcome::Terminated) => break End::Halted,
+            Ok(Outcome::NeedsInput) => break End::NeedsInput,
+            Ok(Outcome::Paused) => break End::OutOfFuel,
+            Ok(Outcome::Error(error)) => break End::Fault(format!("{:?}", error)),
+            Err(message) => break End::Panicked(message),
+        }
+    };
+
+    Run { outputs, end }
+}
+
+fn run_day05(tape: &[i32], inputs: &[i32], fuel: u64) -> Run {
+    let mut vm = Day05VM {
+        memory: tape.to_vec(),
+        input: inputs.iter().copied().collect(),
+        ..Day05VM::default()
+    };
+
+    let mut end = End::OutOfFuel;
+
+    for _ in 0..fuel {
+        // day05's VM panics when it runs out of input instead of suspending,
+        // so catch that case before it happens
+        let next = vm.memory.get(vm.pc).copied().unwrap_or(0);
+        if next % 100 == 3 && vm.input.is_empty() {
+            end = End::NeedsInput;
+            break;
+        }
+
+        match quietly(|| vm.step()) {
+            Ok(true) => {}
+            Ok(false) => {
+                end = End::Halted;
+                break;
+            }
+            Err(message) => {
+                end = End::Fault(message);
+                break;
+            }
+        }
+    }
+
+    Run {
+        outputs: vm.output.iter().map(|value| value.to_string()).collect(),
+        end,
+    }
+}
+
+/// Runs one backend call, turning a panic into its message. Backends are
+/// expected to panic on bad tapes, so their messages aren't printed, but the
+/// silent hook only covers the call so the fuzzer's own panics still show.
+fn quietly<T>(call: impl FnOnce() -> T) -> Result<T, String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(call));
+    panic::set_hook(hook);
+
+    result.map_err(panic_message)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic".to_owned()
+    }
+}
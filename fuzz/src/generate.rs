@@ -0,0 +1,113 @@
+static SEEDS: &[&str] = &[
+    include_str!("../../day05/input.txt"),
+    include_str!("../../day07/input.txt"),
+];
+
+const OPCODES: &[i32] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 99];
+
+/// xorshift64*, which is plenty for picking tapes and keeps runs
+/// reproducible from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero
+        Rng(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        low + self.below((high - low) as usize) as i32
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.below(values.len())]
+    }
+}
+
+pub fn tape(rng: &mut Rng) -> Vec<i32> {
+    if rng.chance(25) {
+        mutate(rng)
+    } else {
+        random_tape(rng)
+    }
+}
+
+pub fn inputs(rng: &mut Rng) -> Vec<i32> {
+    (0..rng.below(4)).map(|_| rng.range(-2, 10)).collect()
+}
+
+/// Builds a tape out of mostly well-formed instructions whose operands point
+/// somewhere near the tape, so that runs get past the first few cells.
+fn random_tape(rng: &mut Rng) -> Vec<i32> {
+    let len = 1 + rng.below(48);
+    let mut tape = Vec::with_capacity(len);
+
+    while tape.len() < len {
+        if rng.chance(10) {
+            tape.push(rng.range(-10, 100));
+            continue;
+        }
+
+        let op = *rng.pick(OPCODES);
+        let arity = match op {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            _ => 0,
+        };
+
+        let mut modes = 0;
+        let mut place = 100;
+        for _ in 0..arity {
+            modes += place * rng.range(0, 3);
+            place *= 10;
+        }
+
+        tape.push(op + modes);
+
+        for _ in 0..arity {
+            let operand = if rng.chance(70) {
+                rng.range(0, len as i32 + 4)
+            } else {
+                rng.range(-3, 20)
+            };
+
+            tape.push(operand);
+        }
+    }
+
+    tape
+}
+
+fn mutate(rng: &mut Rng) -> Vec<i32> {
+    let seed = rng.pick(SEEDS);
+    let mut tape: Vec<i32> = seed.trim().split(',').map(|v| v.parse().unwrap()).collect();
+
+    for _ in 0..1 + rng.below(4) {
+        let index = rng.below(tape.len());
+
+        match rng.below(4) {
+            0 => tape[index] = rng.range(-3, 20),
+            1 => tape[index] = *rng.pick(OPCODES) + 100 * rng.range(0, 3),
+            2 => tape[index] = tape[index].wrapping_add(rng.range(-2, 3)),
+            _ => tape.truncate(index.max(1)),
+        }
+    }
+
+    tape
+}
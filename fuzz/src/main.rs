@@ -0,0 +1,239 @@
+//! Differential fuzzer for the Intcode interpreters in this repo.
+//!
+//! Random and mutated tapes are run through every backend under the same
+//! fuel limit. Whenever the backends disagree on what a tape outputs or how
+//! it stops, the tape is shrunk and reported. Day05's VM predates relative
+//! mode and growable memory, so divergences caused by that are only counted.
+//!
+//!     cargo run -p fuzz -- --iterations 100000 --seed 7 --fuel 10000
+
+mod backends;
+mod generate;
+
+use std::{collections::BTreeMap, process};
+
+use backends::{End, Run, BACKENDS};
+use generate::Rng;
+
+struct Options {
+    iterations: u64,
+    seed: u64,
+    fuel: u64,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let mut options = Options {
+            iterations: 10_000,
+            seed: 1,
+            fuel: 10_000,
+        };
+
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("{} needs a number", arg));
+
+            match arg.as_str() {
+                "--iterations" => options.iterations = value,
+                "--seed" => options.seed = value,
+                "--fuel" => options.fuel = value,
+                _ => panic!("unknown argument {}", arg),
+            }
+        }
+
+        options
+    }
+}
+
+fn run_all(tape: &[i32], inputs: &[i32], fuel: u64) -> Vec<Run> {
+    BACKENDS
+        .iter()
+        .map(|backend| (backend.run)(tape, inputs, fuel))
+        .collect()
+}
+
+/// Groups backends that agree with each other, returning the names in each
+/// group. A tape diverges when there's more than one group.
+fn agreement(runs: &[Run]) -> Vec<Vec<&'static str>> {
+    let mut groups: Vec<(usize, Vec<&'static str>)> = Vec::new();
+
+    for (index, run) in runs.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|(first, _)| runs[*first].agrees_with(run))
+        {
+            Some((_, names)) => names.push(BACKENDS[index].name),
+            None => groups.push((index, vec![BACKENDS[index].name])),
+        }
+    }
+
+    groups.into_iter().map(|(_, names)| names).collect()
+}
+
+/// Divergences that are already understood, so they're counted rather than
+/// shrunk and printed one by one. They're all day05 faulting on something
+/// its VM never supported while every other backend agrees.
+fn known_divergence(runs: &[Run]) -> Option<&'static str> {
+    // day05 is listed first
+    let (day05, others) = runs.split_first()?;
+
+    if !others.windows(2).all(|pair| pair[0].agrees_with(&pair[1])) {
+        return None;
+    }
+
+    let message = match (&day05.end, &others.first()?.end) {
+        (End::Fault(message), _) => message,
+        // the others refuse an immediate or unknown mode on a written
+        // parameter, which day05 never looks at
+        (_, End::Fault(other)) if other.starts_with("IllegalMode") => {
+            return Some("day05 ignores the mode of written parameters")
+        }
+        _ => return None,
+    };
+
+    if message.starts_with("index out of bounds") {
+        Some("day05 doesn't grow memory past the end of the tape")
+    } else if message == "illegal operand mode" {
+        Some("day05 has no relative mode")
+    } else if message == "illegal instruction" {
+        Some("day05 has no ARB instruction")
+    } else {
+        None
+    }
+}
+
+/// Greedily drops and simplifies cells while the backends still split the
+/// same way, to get a tape small enough to reason about.
+fn shrink(mut tape: Vec<i32>, inputs: &[i32], fuel: u64) -> Vec<i32> {
+    let signature = agreement(&run_all(&tape, inputs, fuel));
+    let still_diverges = |candidate: &[i32]| {
+        !candidate.is_empty() && agreement(&run_all(candidate, inputs, fuel)) == signature
+    };
+
+    let mut progress = true;
+    while progress {
+        progress = false;
+
+        for index in (0..tape.len()).rev() {
+            let mut candidate = tape.clone();
+            candidate.remove(index);
+
+            if still_diverges(&candidate) {
+                tape = candidate;
+                progress = true;
+                continue;
+            }
+
+            for simpler in &[0, tape[index] / 2] {
+                if *simpler == tape[index] {
+                    continue;
+                }
+
+                let mut candidate = tape.clone();
+                candidate[index] = *simpler;
+
+                if still_diverges(&candidate) {
+                    tape = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    tape
+}
+
+/// Long-running tapes can print thousands of values, so only show the start.
+fn abbreviate<T: std::fmt::Debug>(values: &[T]) -> String {
+    const SHOWN: usize = 12;
+
+    if values.len() <= SHOWN {
+        format!("{:?}", values)
+    } else {
+        format!("{:?} ... ({} total)", &values[..SHOWN], values.len())
+    }
+}
+
+fn describe(groups: &[Vec<&str>]) -> String {
+    groups
+        .iter()
+        .map(|names| format!("{{{}}}", names.join(", ")))
+        .collect::<Vec<_>>()
+        .join(" vs ")
+}
+
+fn main() {
+    let options = Options::from_args();
+    let mut rng = Rng::new(options.seed);
+
+    let mut divergences: BTreeMap<String, u64> = BTreeMap::new();
+    let mut known: BTreeMap<&str, u64> = BTreeMap::new();
+
+    for iteration in 0..options.iterations {
+        let tape = generate::tape(&mut rng);
+        let inputs = generate::inputs(&mut rng);
+
+        let runs = run_all(&tape, &inputs, options.fuel);
+        let groups = agreement(&runs);
+
+        if groups.len() == 1 {
+            continue;
+        }
+
+        if let Some(reason) = known_divergence(&runs) {
+            *known.entry(reason).or_insert(0) += 1;
+            continue;
+        }
+
+        let signature = describe(&groups);
+        let count = divergences.entry(signature.clone()).or_insert(0);
+        *count += 1;
+
+        if *count > 1 {
+            continue;
+        }
+
+        let tape = shrink(tape, &inputs, options.fuel);
+        let runs = run_all(&tape, &inputs, options.fuel);
+
+        println!("Divergence at iteration {}: {}", iteration, signature);
+        println!("  tape:   {}", abbreviate(&tape));
+        println!("  inputs: {:?}", inputs);
+
+        for (backend, run) in BACKENDS.iter().zip(&runs) {
+            println!(
+                "  {:<16} {:?} {}",
+                backend.name,
+                run.end,
+                abbreviate(&run.outputs)
+            );
+        }
+
+        println!();
+    }
+
+    if !known.is_empty() {
+        println!("Known divergences, not reported above:");
+        for (reason, count) in &known {
+            println!("{:>8}  {}", count, reason);
+        }
+        println!();
+    }
+
+    if divergences.is_empty() {
+        println!("No divergences in {} tapes", options.iterations);
+        return;
+    }
+
+    println!("Summary:");
+    for (signature, count) in &divergences {
+        println!("{:>8}  {}", count, signature);
+    }
+
+    process::exit(1);
+}
@@ -0,0 +1,415 @@
+//! An auto-player for the text adventure from day 25. The program describes
+//! rooms like this, then waits for a command like `north` or `take mug`:
+//!
+//! ```text
+//! == Hull Breach ==
+//! You got in through a hole in the floor here.
+//!
+//! Doors here lead:
+//! - north
+//! - east
+//!
+//! Items here:
+//! - mug
+//!
+//! Command?
+//! ```
+//!
+//! Somewhere there's a security checkpoint guarding a pressure-sensitive
+//! floor, which only lets through a droid carrying the right weight of
+//! items. Some items are deadly to pick up, so every risky step is tried on
+//! a snapshot of the game first, which for a machine is just a clone.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+/// How much a single command can print before the game is assumed to be
+/// stuck printing forever.
+pub const OUTPUT_LIMIT: usize = 10_000;
+
+/// How many instructions a single command can run before the game is
+/// assumed to be stuck in a loop.
+pub const STEP_LIMIT: u64 = 10_000_000;
+
+/// Every combination of items gets its own snapshot, so past this many
+/// items there are too many to try.
+pub const MAX_ITEMS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdventureError {
+    Fault(Error),
+
+    /// A command printed more than `OUTPUT_LIMIT` characters or ran more
+    /// than `STEP_LIMIT` instructions.
+    Runaway,
+
+    /// The game ended while there was still exploring to do.
+    Ended(String),
+
+    /// The game printed something that didn't have a room in it.
+    NoRoom(String),
+
+    NoCheckpoint,
+
+    /// No set of items got past the pressure-sensitive floor.
+    NoCombination,
+
+    /// The droid is carrying more than `MAX_ITEMS` items.
+    TooManyItems(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+/// Every room described in some output, in order. Being thrown out of a
+/// room prints two: the one that threw the droid out and the one it landed
+/// in.
+pub fn parse_rooms(text: &str) -> Vec<Room> {
+    let mut rooms = Vec::new();
+    let mut list = None;
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("== ") && line.ends_with(" ==") {
+            rooms.push(Room {
+                name: line[3..line.len() - 3].to_owned(),
+                ..Room::default()
+            });
+            list = None;
+            continue;
+        }
+
+        let room = match rooms.last_mut() {
+            Some(room) => room,
+            None => continue,
+        };
+
+        match line {
+            "Doors here lead:" => list = Some(true),
+            "Items here:" => list = Some(false),
+            "" => list = None,
+            _ if line.starts_with("- ") => match list {
+                Some(true) => room.doors.push(line[2..].to_owned()),
+                Some(false) => room.items.push(line[2..].to_owned()),
+                None => {}
+            },
+            _ if room.description.is_empty() && list.is_none() => {
+                room.description = line.to_owned();
+            }
+            _ => {}
+        }
+    }
+
+    rooms
+}
+
+/// Pulls the password out of the game's last words, which is the longest
+/// run of digits in them.
+pub fn password(text: &str) -> Option<&str> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .max_by_key(|run| run.len())
+}
+
+/// A game in progress, with the room the droid is standing in.
+#[derive(Clone)]
+pub struct Game<M: IntcodeMachine> {
+    pub machine: M,
+    pub room: Room,
+
+    /// Set once the program stops, whether the droid won or died.
+    pub finished: bool,
+}
+
+impl<M: IntcodeMachine> Game<M> {
+    /// Starts the game and reads the first room.
+    pub fn new(machine: M) -> Result<Self, AdventureError> {
+        let mut game = Game {
+            machine,
+            room: Room::default(),
+            finished: false,
+        };
+
+        let text = game.read()?;
+        match parse_rooms(&text).pop() {
+            Some(room) => game.room = room,
+            None => return Err(AdventureError::NoRoom(text)),
+        }
+
+        Ok(game)
+    }
+
+    /// Runs until the game asks for a command or stops.
+    fn read(&mut self) -> Result<String, AdventureError> {
+        let mut text = String::new();
+        self.machine.set_fuel(Some(STEP_LIMIT));
+
+        loop {
+            match self.machine.run() {
+                Outcome::NeedsInput => return Ok(text),
+                Outcome::Terminated => {
+                    self.finished = true;
+                    return Ok(text);
+                }
+                // the fuel is the only thing pausing the machine
+                Outcome::Paused => return Err(AdventureError::Runaway),
+                Outcome::SentOutput => {
+                    for value in self.machine.drain_output() {
+                        if let Some(code @ 0..=127) = value.to_i64() {
+                            text.push(code as u8 as char);
+                        }
+                    }
+
+                    if text.len() > OUTPUT_LIMIT {
+                        return Err(AdventureError::Runaway);
+                    }
+                }
+                Outcome::Error(error) => return Err(AdventureError::Fault(error)),
+            }
+        }
+    }
+
+    /// Sends a command and returns what the game printed back. If the
+    /// droid ended up somewhere new, `room` follows it.
+    pub fn send(&mut self, command: &str) -> Result<String, AdventureError> {
+        if self.finished {
+            return Err(AdventureError::Ended(String::new()));
+        }
+
+        for byte in command.bytes().chain(Some(b'\n')) {
+            self.machine.push_input(M::Word::from(byte as i32));
+        }
+
+        let text = self.read()?;
+
+        if let Some(room) = parse_rooms(&text).pop() {
+            self.room = room;
+        }
+
+        Ok(text)
+    }
+}
+
+/// Everything learned about the ship.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Map {
+    /// Every room found, in the order they were found.
+    pub rooms: Vec<Room>,
+
+    /// Where each door of each room leads.
+    pub links: HashMap<String, HashMap<String, String>>,
+
+    /// Items that kill the droid, trap it, or never let the game continue.
+    pub deadly: HashSet<String>,
+
+    /// The room with the pressure-sensitive floor next to it, and the door
+    /// that leads there.
+    pub checkpoint: Option<(String, String)>,
+}
+
+impl Map {
+    /// The items worth carrying, and the rooms they're in.
+    pub fn safe_items(&self) -> Vec<(String, String)> {
+        self.rooms
+            .iter()
+            .flat_map(|room| {
+                room.items
+                    .iter()
+                    .map(move |item| (room.name.clone(), item.clone()))
+            })
+            .filter(|(_, item)| !self.deadly.contains(item))
+            .collect()
+    }
+
+    /// The doors to go through to get from one room to another.
+    pub fn route(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut came_from: HashMap<&str, (&str, &str)> = HashMap::new();
+        let mut to_visit = VecDeque::new();
+        to_visit.push_back(from);
+
+        while let Some(room) = to_visit.pop_front() {
+            if room == to {
+                let mut route = Vec::new();
+                let mut current = room;
+
+                while let Some(&(previous, door)) = came_from.get(current) {
+                    route.push(door.to_owned());
+                    current = previous;
+                }
+
+                route.reverse();
+                return Some(route);
+            }
+
+            for (door, next) in self.links.get(room).into_iter().flatten() {
+                if next != from && !came_from.contains_key(next.as_str()) {
+                    came_from.insert(next, (room, door));
+                    to_visit.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Whether picking up an item leaves the droid alive and able to move, found
+/// out on a snapshot.
+fn is_safe<M: IntcodeMachine + Clone>(game: &Game<M>, item: &str) -> Result<bool, AdventureError> {
+    let mut fork = game.clone();
+
+    let moved =
+        fork.send(&format!("take {}", item))
+            .and_then(|_| match fork.room.doors.first().cloned() {
+                Some(door) => fork.send(&door),
+                None => Ok(String::new()),
+            });
+
+    match moved {
+        Ok(text) => Ok(!fork.finished && !parse_rooms(&text).is_empty()),
+        Err(AdventureError::Runaway) | Err(AdventureError::Ended(_)) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Visits every room by trying every door on a snapshot, noting which
+/// items are deadly along the way. The game itself isn't touched.
+pub fn explore<M: IntcodeMachine + Clone>(game: &Game<M>) -> Result<Map, AdventureError> {
+    let mut map = Map::default();
+    let mut to_visit = VecDeque::new();
+
+    map.rooms.push(game.room.clone());
+    to_visit.push_back(game.clone());
+
+    while let Some(game) = to_visit.pop_front() {
+        let here = game.room.clone();
+
+        for item in &here.items {
+            if !is_safe(&game, item)? {
+                map.deadly.insert(item.clone());
+            }
+        }
+
+        for door in &here.doors {
+            let mut fork = game.clone();
+            let text = fork.send(door)?;
+            let rooms = parse_rooms(&text);
+
+            if fork.finished {
+                return Err(AdventureError::Ended(text));
+            }
+
+            let next = match rooms.last() {
+                Some(next) => next,
+                None => return Err(AdventureError::NoRoom(text)),
+            };
+
+            // thrown straight back out, so that's the floor
+            if rooms.len() > 1 && next.name == here.name {
+                map.checkpoint = Some((here.name.clone(), door.clone()));
+                continue;
+            }
+
+            map.links
+                .entry(here.name.clone())
+                .or_default()
+                .insert(door.clone(), next.name.clone());
+
+            if map.rooms.iter().all(|room| room.name != next.name) {
+                map.rooms.push(next.clone());
+                to_visit.push_back(fork);
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+fn walk<M: IntcodeMachine>(game: &mut Game<M>, map: &Map, to: &str) -> Result<(), AdventureError> {
+    let route = map
+        .route(&game.room.name, to)
+        .ok_or_else(|| AdventureError::NoRoom(to.to_owned()))?;
+
+    for door in route {
+        game.send(&door)?;
+    }
+
+    Ok(())
+}
+
+/// Picks up every safe item and heads to the checkpoint. Returns what the
+/// droid is carrying.
+pub fn collect<M: IntcodeMachine>(
+    game: &mut Game<M>,
+    map: &Map,
+) -> Result<Vec<String>, AdventureError> {
+    let mut carrying = Vec::new();
+
+    for (room, item) in map.safe_items() {
+        walk(game, map, &room)?;
+        game.send(&format!("take {}", item))?;
+        carrying.push(item);
+    }
+
+    let (checkpoint, _) = map
+        .checkpoint
+        .as_ref()
+        .ok_or(AdventureError::NoCheckpoint)?;
+    walk(game, map, checkpoint)?;
+
+    Ok(carrying)
+}
+
+/// Tries every set of carried items on the floor, each on its own snapshot,
+/// until one isn't thrown back. Returns what the game said then.
+pub fn crack<M: IntcodeMachine + Clone>(
+    game: &Game<M>,
+    map: &Map,
+    carrying: &[String],
+) -> Result<String, AdventureError> {
+    let (checkpoint, door) = map
+        .checkpoint
+        .as_ref()
+        .ok_or(AdventureError::NoCheckpoint)?;
+
+    if carrying.len() > MAX_ITEMS {
+        return Err(AdventureError::TooManyItems(carrying.len()));
+    }
+
+    for keep in 0..1u32 << carrying.len() {
+        let mut fork = game.clone();
+
+        for (index, item) in carrying.iter().enumerate() {
+            if keep & (1 << index) == 0 {
+                fork.send(&format!("drop {}", item))?;
+            }
+        }
+
+        let text = fork.send(door)?;
+        if fork.finished || &fork.room.name != checkpoint {
+            return Ok(text);
+        }
+    }
+
+    Err(AdventureError::NoCombination)
+}
+
+/// Plays the whole game: maps the ship, picks up everything safe, and gets
+/// past the checkpoint. Returns the game's last words, which hold the
+/// password.
+pub fn play<M: IntcodeMachine + Clone>(machine: M) -> Result<String, AdventureError> {
+    let mut game = Game::new(machine)?;
+    let map = explore(&game)?;
+    let carrying = collect(&mut game, &map)?;
+
+    crack(&game, &map, &carrying)
+}
@@ -0,0 +1,252 @@
+//! The arcade cabinet from day 13. The program draws by outputting
+//! `(x, y, tile)` triples, except that `(-1, 0, score)` sets the score, and
+//! reads the joystick whenever it wants the player to move.
+//!
+//! Day 13 part two plays for free after writing 2 to address 0, which is up
+//! to the caller before handing the program over.
+
+use std::collections::HashMap;
+
+use crate::{
+    grid,
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    fn from_id(id: Option<i64>) -> Option<Tile> {
+        match id? {
+            0 => Some(Tile::Empty),
+            1 => Some(Tile::Wall),
+            2 => Some(Tile::Block),
+            3 => Some(Tile::Paddle),
+            4 => Some(Tile::Ball),
+            _ => None,
+        }
+    }
+
+    fn glyph(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '#',
+            Tile::Block => '+',
+            Tile::Paddle => '=',
+            Tile::Ball => 'o',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Joystick {
+    Left,
+    Neutral,
+    Right,
+}
+
+impl Joystick {
+    fn value(self) -> i32 {
+        match self {
+            Joystick::Left => -1,
+            Joystick::Neutral => 0,
+            Joystick::Right => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcadeError {
+    Fault(Error),
+
+    /// The program drew a tile id that isn't one of the five tiles.
+    InvalidTile(Option<i64>),
+
+    /// The machine paused, like when its fuel ran out, before asking for input.
+    Paused,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Screen {
+    pub tiles: HashMap<(i64, i64), Tile>,
+    pub score: i64,
+}
+
+impl Screen {
+    fn draw(
+        &mut self,
+        x: Option<i64>,
+        y: Option<i64>,
+        value: Option<i64>,
+    ) -> Result<(), ArcadeError> {
+        match (x, y) {
+            (Some(-1), Some(0)) => self.score = value.unwrap_or(0),
+            (Some(x), Some(y)) => {
+                let tile = Tile::from_id(value).ok_or(ArcadeError::InvalidTile(value))?;
+                self.tiles.insert((x, y), tile);
+            }
+            _ => return Err(ArcadeError::InvalidTile(value)),
+        }
+
+        Ok(())
+    }
+
+    pub fn count(&self, tile: Tile) -> usize {
+        self.tiles.values().filter(|&&other| other == tile).count()
+    }
+
+    /// Where a tile is drawn, if it's anywhere. For the ball and paddle,
+    /// which there's only one of.
+    pub fn find(&self, tile: Tile) -> Option<(i64, i64)> {
+        self.tiles
+            .iter()
+            .find(|(_, &other)| other == tile)
+            .map(|(&position, _)| position)
+    }
+
+    /// Draws the screen as text, with the score underneath.
+    pub fn render(&self) -> String {
+        let mut output = grid::render(&self.tiles, |_, tile| {
+            tile.copied().unwrap_or(Tile::Empty).glyph()
+        });
+
+        output.push_str(&format!("Score: {}\n", self.score));
+        output
+    }
+}
+
+/// Moves the paddle towards the ball, which is enough to never miss.
+pub fn track_ball(screen: &Screen) -> Joystick {
+    match (screen.find(Tile::Paddle), screen.find(Tile::Ball)) {
+        (Some((paddle, _)), Some((ball, _))) if ball < paddle => Joystick::Left,
+        (Some((paddle, _)), Some((ball, _))) if ball > paddle => Joystick::Right,
+        _ => Joystick::Neutral,
+    }
+}
+
+/// A game program hooked up to a screen.
+pub struct Cabinet<M: IntcodeMachine> {
+    pub machine: M,
+    pub screen: Screen,
+
+    /// Every joystick move so far, for replaying the game later.
+    pub moves: Vec<Joystick>,
+
+    /// Outputs that haven't made up a whole triple yet.
+    pending: Vec<Option<i64>>,
+}
+
+impl<M: IntcodeMachine> Cabinet<M> {
+    pub fn new(machine: M) -> Self {
+        Cabinet {
+            machine,
+            screen: Screen::default(),
+            moves: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Runs the game until it reads the joystick, returning true, or ends,
+    /// returning false.
+    pub fn run_until_input(&mut self) -> Result<bool, ArcadeError> {
+        loop {
+            match self.machine.run() {
+                Outcome::Terminated => return Ok(false),
+                Outcome::NeedsInput => return Ok(true),
+                Outcome::SentOutput => {
+                    let outputs = self.machine.drain_output();
+                    self.pending.extend(outputs.iter().map(Word::to_i64));
+
+                    while self.pending.len() >= 3 {
+                        let triple: Vec<_> = self.pending.drain(..3).collect();
+                        self.screen.draw(triple[0], triple[1], triple[2])?;
+                    }
+                }
+                Outcome::Paused => return Err(ArcadeError::Paused),
+                Outcome::Error(error) => return Err(ArcadeError::Fault(error)),
+            }
+        }
+    }
+
+    pub fn push(&mut self, joystick: Joystick) {
+        self.machine.push_input(M::Word::from(joystick.value()));
+        self.moves.push(joystick);
+    }
+
+    /// Plays the game to the end, asking `player` for a move whenever the
+    /// game wants one. Returns the final score.
+    pub fn play(
+        &mut self,
+        mut player: impl FnMut(&Screen) -> Joystick,
+    ) -> Result<i64, ArcadeError> {
+        while self.run_until_input()? {
+            let joystick = player(&self.screen);
+            self.push(joystick);
+        }
+
+        Ok(self.screen.score)
+    }
+}
+
+/// Plays back recorded joystick moves one at a time, yielding the screen as
+/// the game shows it before each move and once more at the end.
+pub struct Replay<M: IntcodeMachine> {
+    cabinet: Cabinet<M>,
+    moves: Vec<Joystick>,
+    next: usize,
+    finished: bool,
+}
+
+impl<M: IntcodeMachine> Replay<M> {
+    pub fn new(machine: M, moves: &[Joystick]) -> Self {
+        Replay {
+            cabinet: Cabinet::new(machine),
+            moves: moves.to_vec(),
+            next: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<M: IntcodeMachine> Iterator for Replay<M> {
+    type Item = Result<Screen, ArcadeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if self.next > 0 {
+            match self.moves.get(self.next - 1) {
+                Some(&joystick) => self.cabinet.push(joystick),
+                None => {
+                    // the recording ran out before the game ended
+                    self.finished = true;
+                    return None;
+                }
+            }
+        }
+
+        self.next += 1;
+
+        match self.cabinet.run_until_input() {
+            Ok(true) => Some(Ok(self.cabinet.screen.clone())),
+            Ok(false) => {
+                self.finished = true;
+                Some(Ok(self.cabinet.screen.clone()))
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
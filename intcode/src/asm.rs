@@ -0,0 +1,652 @@
+//! An assembler for Intcode, producing relocatable objects for the linker.
+//!
+//! Each line holds an optional label, then an instruction or directive.
+//! Everything after a `;` is a comment.
+//!
+//! ```text
+//! .export double
+//!
+//! ; double(n) -> n * 2
+//! double:
+//!     mul [rb+1], 2, [rb+1]
+//!     ret
+//! ```
+//!
+//! Operands are immediate values (`5`, `'a'`, `label`), positions
+//! (`[label]`, `[label+3]`) or relative to the relative base (`[rb]`,
+//! `[rb+2]`, `[rb-1]`). A number, character or label can be offset by adding
+//! or subtracting numbers.
+//!
+//! Labels starting with `.` are local to the label before them, so every
+//! function can have its own `.loop`.
+//!
+//! The mnemonics are the opcode names used by the VM: `add`, `mul`, `inn`,
+//! `out`, `jit`, `jif`, `cml`, `cme`, `arb` and `end`. There are also a few
+//! pseudo-instructions:
+//!
+//! - `mov a, b` copies a into b
+//! - `jmp target` always jumps
+//! - `call function, frame` calls a function, see below
+//! - `ret` returns from a function
+//! - `data a, b, ...` places raw values
+//!
+//! `.export name, ...` makes labels visible to other objects and
+//! `.import name, ...` declares labels that another object will provide.
+//!
+//! # Calling convention
+//!
+//! The relative base points at the current function's frame. `[rb]` holds
+//! the return address, followed by the arguments and then any locals the
+//! function wants. A function returns its result in `[rb+1]`. Frames are
+//! at least one cell long, for the return address.
+//!
+//! To call a function, a caller whose own frame is `frame` cells long stores
+//! the arguments at `[rb+frame+1]` onwards and runs `call function, frame`.
+//! The result is in `[rb+frame+1]` once it returns.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::vm::{
+    MODE_IMM, MODE_POS, MODE_REL, OP_ADD, OP_ARB, OP_CME, OP_CML, OP_END, OP_INN, OP_JIF, OP_JIT,
+    OP_MUL, OP_OUT,
+};
+
+/// Every built-in instruction's mnemonic, opcode, number of operands, and
+/// which operands it stores into.
+pub(crate) static INSTRUCTIONS: &[(&str, u8, usize, &[usize])] = &[
+    ("add", OP_ADD, 3, &[2]),
+    ("mul", OP_MUL, 3, &[2]),
+    ("inn", OP_INN, 1, &[0]),
+    ("out", OP_OUT, 1, &[]),
+    ("jit", OP_JIT, 2, &[]),
+    ("jif", OP_JIF, 2, &[]),
+    ("cml", OP_CML, 3, &[2]),
+    ("cme", OP_CME, 3, &[2]),
+    ("arb", OP_ARB, 1, &[]),
+    ("end", OP_END, 0, &[]),
+];
+
+/// Assembled code that hasn't been given an address yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Object {
+    pub code: Vec<i64>,
+
+    /// Cells holding addresses inside this object. The linker adds the
+    /// object's final address to each of them.
+    pub relocations: Vec<usize>,
+
+    /// Cells referring to labels from other objects. The linker adds the
+    /// label's final address to each of them.
+    pub imports: Vec<(usize, String)>,
+
+    /// Labels other objects can refer to, by offset into `code`.
+    pub exports: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsmError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    UnknownInstruction {
+        line: usize,
+        name: String,
+    },
+    WrongOperandCount {
+        line: usize,
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+
+    /// An instruction would store into an immediate operand.
+    ImmediateWrite {
+        line: usize,
+    },
+    DuplicateLabel {
+        line: usize,
+        name: String,
+    },
+    UndefinedLabel {
+        line: usize,
+        name: String,
+    },
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsmError::Syntax { line, message } => write!(formatter, "line {}: {}", line, message),
+            AsmError::UnknownInstruction { line, name } => {
+                write!(formatter, "line {}: unknown instruction {}", line, name)
+            }
+            AsmError::WrongOperandCount {
+                line,
+                name,
+                expected,
+                found,
+            } => write!(
+                formatter,
+                "line {}: {} takes {} operands, found {}",
+                line, name, expected, found
+            ),
+            AsmError::ImmediateWrite { line } => {
+                write!(formatter, "line {}: can't store into an immediate", line)
+            }
+            AsmError::DuplicateLabel { line, name } => {
+                write!(formatter, "line {}: {} is already defined", line, name)
+            }
+            AsmError::UndefinedLabel { line, name } => {
+                write!(formatter, "line {}: {} is never defined", line, name)
+            }
+        }
+    }
+}
+
+/// A value that may need a label's address added to it once it's known.
+struct Expr {
+    value: i64,
+    label: Option<String>,
+}
+
+struct Operand {
+    mode: u8,
+    expr: Expr,
+}
+
+struct Assembler {
+    object: Object,
+    line: usize,
+
+    /// The last label not starting with `.`, which local labels belong to.
+    scope: String,
+
+    labels: HashMap<String, usize>,
+    imports: HashSet<String>,
+    exports: Vec<(usize, String)>,
+
+    /// Cells waiting for a label's address, with the line they came from.
+    fixups: Vec<(usize, usize, String)>,
+}
+
+pub fn assemble(source: &str) -> Result<Object, AsmError> {
+    let mut asm = Assembler {
+        object: Object::default(),
+        line: 0,
+        scope: String::new(),
+        labels: HashMap::new(),
+        imports: HashSet::new(),
+        exports: Vec::new(),
+        fixups: Vec::new(),
+    };
+
+    for (index, line) in source.lines().enumerate() {
+        asm.line = index + 1;
+        asm.assemble_line(strip_comment(line))?;
+    }
+
+    asm.finish()
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_char = false;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_char => escaped = true,
+            '\'' => in_char = !in_char,
+            ';' if !in_char => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.' => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Splits on commas that aren't inside a character literal.
+fn split_operands(text: &str) -> Vec<&str> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut operands = Vec::new();
+    let mut start = 0;
+    let mut in_char = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_char => escaped = true,
+            '\'' => in_char = !in_char,
+            ',' if !in_char => {
+                operands.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    operands.push(text[start..].trim());
+    operands
+}
+
+impl Assembler {
+    fn syntax(&self, message: impl Into<String>) -> AsmError {
+        AsmError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn assemble_line(&mut self, mut line: &str) -> Result<(), AsmError> {
+        loop {
+            line = line.trim();
+
+            let label = match line.find(':') {
+                Some(end) if is_identifier(&line[..end]) => &line[..end],
+                _ => break,
+            };
+
+            self.define_label(label)?;
+            line = &line[label.len() + 1..];
+        }
+
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let (name, rest) = match line.find(char::is_whitespace) {
+            Some(end) => (&line[..end], &line[end..]),
+            None => (line, ""),
+        };
+
+        let operands = split_operands(rest);
+
+        match name {
+            ".export" | ".import" => {
+                for &symbol in &operands {
+                    if !is_identifier(symbol) || symbol.starts_with('.') {
+                        return Err(self.syntax(format!("{} isn't a global label", symbol)));
+                    }
+
+                    if name == ".export" {
+                        self.exports.push((self.line, symbol.to_owned()));
+                    } else {
+                        self.imports.insert(symbol.to_owned());
+                    }
+                }
+
+                Ok(())
+            }
+            "data" => {
+                for operand in operands {
+                    let expr = self.expr(operand)?;
+                    self.emit(expr);
+                }
+
+                Ok(())
+            }
+            _ => self.instruction(name, &operands),
+        }
+    }
+
+    fn define_label(&mut self, label: &str) -> Result<(), AsmError> {
+        let name = self.qualify(label);
+
+        if !label.starts_with('.') {
+            self.scope = name.clone();
+        }
+
+        if self.labels.contains_key(&name) {
+            return Err(AsmError::DuplicateLabel {
+                line: self.line,
+                name,
+            });
+        }
+
+        self.labels.insert(name, self.object.code.len());
+        Ok(())
+    }
+
+    fn qualify(&self, label: &str) -> String {
+        if label.starts_with('.') {
+            format!("{}{}", self.scope, label)
+        } else {
+            label.to_owned()
+        }
+    }
+
+    fn instruction(&mut self, name: &str, operands: &[&str]) -> Result<(), AsmError> {
+        let expect = |expected: usize| {
+            if operands.len() == expected {
+                Ok(())
+            } else {
+                Err(AsmError::WrongOperandCount {
+                    line: self.line,
+                    name: name.to_owned(),
+                    expected,
+                    found: operands.len(),
+                })
+            }
+        };
+
+        match name {
+            "mov" => {
+                expect(2)?;
+                let source = self.operand(operands[0])?;
+                let dest = self.operand(operands[1])?;
+                return self.emit_instruction(OP_ADD, vec![source, immediate(0), dest], &[2]);
+            }
+            "jmp" => {
+                expect(1)?;
+                let dest = self.operand(operands[0])?;
+                return self.emit_instruction(OP_JIT, vec![immediate(1), dest], &[]);
+            }
+            "ret" => {
+                expect(0)?;
+                let dest = Operand {
+                    mode: MODE_REL,
+                    expr: Expr {
+                        value: 0,
+                        label: None,
+                    },
+                };
+                return self.emit_instruction(OP_JIF, vec![immediate(0), dest], &[]);
+            }
+            "call" => {
+                expect(2)?;
+                return self.call(operands[0], operands[1]);
+            }
+            _ => {}
+        }
+
+        let &(_, op, arity, writes) = INSTRUCTIONS
+            .iter()
+            .find(|(mnemonic, ..)| *mnemonic == name)
+            .ok_or_else(|| AsmError::UnknownInstruction {
+                line: self.line,
+                name: name.to_owned(),
+            })?;
+
+        expect(arity)?;
+
+        let operands = operands
+            .iter()
+            .map(|operand| self.operand(operand))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.emit_instruction(op, operands, writes)
+    }
+
+    fn call(&mut self, function: &str, frame: &str) -> Result<(), AsmError> {
+        let function = self.operand(function)?;
+        let frame = match self.expr(frame)? {
+            Expr { value, label: None } => value,
+            Expr { label: Some(_), .. } => return Err(self.syntax("frame size must be a number")),
+        };
+
+        // the return address is the cell after the jump, which depends on
+        // whether the relative base needs moving
+        let mut return_address = self.object.code.len() + 4 + 3;
+        if frame != 0 {
+            return_address += 2;
+        }
+
+        self.emit_instruction(
+            OP_ADD,
+            vec![
+                immediate(0),
+                immediate(0),
+                Operand {
+                    mode: MODE_REL,
+                    expr: Expr {
+                        value: frame,
+                        label: None,
+                    },
+                },
+            ],
+            &[2],
+        )?;
+
+        let cell = self.object.code.len() - 3;
+        self.object.code[cell] = return_address as i64;
+        self.object.relocations.push(cell);
+
+        if frame != 0 {
+            self.emit_instruction(OP_ARB, vec![immediate(frame)], &[])?;
+        }
+
+        self.emit_instruction(OP_JIF, vec![immediate(0), function], &[])?;
+
+        if frame != 0 {
+            self.emit_instruction(OP_ARB, vec![immediate(-frame)], &[])?;
+        }
+
+        Ok(())
+    }
+
+    fn emit_instruction(
+        &mut self,
+        op: u8,
+        operands: Vec<Operand>,
+        writes: &[usize],
+    ) -> Result<(), AsmError> {
+        let mut instruction = op as i64;
+        let mut place = 100;
+
+        for (index, operand) in operands.iter().enumerate() {
+            if operand.mode == MODE_IMM && writes.contains(&index) {
+                return Err(AsmError::ImmediateWrite { line: self.line });
+            }
+
+            instruction += operand.mode as i64 * place;
+            place *= 10;
+        }
+
+        self.object.code.push(instruction);
+
+        for operand in operands {
+            self.emit(operand.expr);
+        }
+
+        Ok(())
+    }
+
+    fn emit(&mut self, expr: Expr) {
+        if let Some(label) = expr.label {
+            self.fixups
+                .push((self.object.code.len(), self.line, self.qualify(&label)));
+        }
+
+        self.object.code.push(expr.value);
+    }
+
+    fn operand(&self, text: &str) -> Result<Operand, AsmError> {
+        let inner = match text.strip_prefix('[') {
+            Some(inner) => inner
+                .strip_suffix(']')
+                .ok_or_else(|| self.syntax(format!("unclosed bracket in {}", text)))?
+                .trim(),
+            None => {
+                return Ok(Operand {
+                    mode: MODE_IMM,
+                    expr: self.expr(text)?,
+                })
+            }
+        };
+
+        let offset = match inner.strip_prefix("rb") {
+            Some(offset) if !offset.starts_with(|c: char| c.is_alphanumeric() || c == '_') => {
+                offset.trim()
+            }
+            _ => {
+                return Ok(Operand {
+                    mode: MODE_POS,
+                    expr: self.expr(inner)?,
+                })
+            }
+        };
+
+        let value = if offset.is_empty() {
+            0
+        } else {
+            match self.expr(&format!("0{}", offset))? {
+                Expr { value, label: None } => value,
+                Expr { label: Some(_), .. } => {
+                    return Err(self.syntax("relative offsets must be numbers"))
+                }
+            }
+        };
+
+        Ok(Operand {
+            mode: MODE_REL,
+            expr: Expr { value, label: None },
+        })
+    }
+
+    /// Parses a sum like `label+3`, `'a'` or `-12`. At most one label is
+    /// allowed and it can't be negated, since only its address gets patched.
+    fn expr(&self, text: &str) -> Result<Expr, AsmError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(self.syntax("missing operand"));
+        }
+
+        let mut expr = Expr {
+            value: 0,
+            label: None,
+        };
+        let mut rest = text;
+        let mut negative = false;
+
+        if let Some(stripped) = rest.strip_prefix('-') {
+            negative = true;
+            rest = stripped.trim_start();
+        }
+
+        loop {
+            let (term, after) = self.term(rest, text)?;
+
+            match term {
+                Expr {
+                    value,
+                    label: Some(label),
+                } => {
+                    if negative || expr.label.is_some() {
+                        return Err(self.syntax(format!("can't compute {}", text)));
+                    }
+
+                    expr.value += value;
+                    expr.label = Some(label);
+                }
+                Expr { value, label: None } => {
+                    expr.value += if negative { -value } else { value };
+                }
+            }
+
+            rest = after.trim_start();
+
+            if rest.is_empty() {
+                return Ok(expr);
+            } else if let Some(stripped) = rest.strip_prefix('+') {
+                negative = false;
+                rest = stripped.trim_start();
+            } else if let Some(stripped) = rest.strip_prefix('-') {
+                negative = true;
+                rest = stripped.trim_start();
+            } else {
+                return Err(self.syntax(format!("unexpected {} in {}", rest, text)));
+            }
+        }
+    }
+
+    /// Parses one number, character or label from the start of `text`.
+    fn term<'a>(&self, text: &'a str, whole: &str) -> Result<(Expr, &'a str), AsmError> {
+        let invalid = || self.syntax(format!("invalid operand {}", whole));
+
+        if let Some(rest) = text.strip_prefix('\'') {
+            let (value, len) = match rest.as_bytes() {
+                [b'\\', b'n', b'\'', ..] => (b'\n', 2),
+                [b'\\', b't', b'\'', ..] => (b'\t', 2),
+                [b'\\', b'0', b'\'', ..] => (0, 2),
+                [b'\\', c @ b'\\', b'\'', ..] | [b'\\', c @ b'\'', b'\'', ..] => (*c, 2),
+                [c, b'\'', ..] if c.is_ascii() && *c != b'\\' => (*c, 1),
+                _ => return Err(invalid()),
+            };
+
+            let expr = Expr {
+                value: value as i64,
+                label: None,
+            };
+            return Ok((expr, &rest[len + 1..]));
+        }
+
+        let end = text
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(text.len());
+        let (word, rest) = text.split_at(end);
+
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            let value = word.parse().map_err(|_| invalid())?;
+            Ok((Expr { value, label: None }, rest))
+        } else if is_identifier(word) {
+            let expr = Expr {
+                value: 0,
+                label: Some(word.to_owned()),
+            };
+            Ok((expr, rest))
+        } else {
+            Err(invalid())
+        }
+    }
+
+    fn finish(mut self) -> Result<Object, AsmError> {
+        for (cell, line, label) in self.fixups {
+            if let Some(&address) = self.labels.get(&label) {
+                self.object.code[cell] += address as i64;
+                self.object.relocations.push(cell);
+            } else if self.imports.contains(&label) {
+                self.object.imports.push((cell, label));
+            } else {
+                return Err(AsmError::UndefinedLabel { line, name: label });
+            }
+        }
+
+        for (line, name) in self.exports {
+            match self.labels.get(&name) {
+                Some(&address) => {
+                    self.object.exports.insert(name, address);
+                }
+                None => return Err(AsmError::UndefinedLabel { line, name }),
+            }
+        }
+
+        self.object.relocations.sort_unstable();
+        Ok(self.object)
+    }
+}
+
+fn immediate(value: i64) -> Operand {
+    Operand {
+        mode: MODE_IMM,
+        expr: Expr { value, label: None },
+    }
+}
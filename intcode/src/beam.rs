@@ -0,0 +1,98 @@
+//! Searches over the tractor beam from day 19. The drone system reads a
+//! position and outputs 1 if it's inside the beam, running from scratch for
+//! every position, so every search goes through a `QueryOracle`.
+//!
+//! The beam is a cone coming out of (0, 0), so each row is one run of pulled
+//! positions whose edges only ever move right going down. That lets the
+//! searches here follow the edges instead of scanning whole rows.
+
+use std::hash::Hash;
+
+use crate::{
+    machine::IntcodeMachine,
+    oracle::{QueryError, QueryOracle},
+    word::Word,
+};
+
+/// How far right of the last edge to look in a row before deciding the
+/// beam is too thin to show up in it. Rows close to the emitter can miss
+/// the beam entirely.
+const ROW_SEARCH: i64 = 32;
+
+pub fn pulled<M>(oracle: &mut QueryOracle<M>, x: i64, y: i64) -> Result<bool, QueryError>
+where
+    M: IntcodeMachine + Clone,
+    M::Word: Hash + Eq,
+{
+    let word = |value| M::Word::from_i64(value).ok_or(QueryError::InputTooLarge(value));
+    let inputs = [word(x)?, word(y)?];
+
+    Ok(oracle.ask(&inputs)?.to_i64() == Some(1))
+}
+
+/// How many positions are pulled in the `size` by `size` square at the
+/// emitter, for part one. This asks about every position.
+pub fn count_pulled<M>(oracle: &mut QueryOracle<M>, size: i64) -> Result<usize, QueryError>
+where
+    M: IntcodeMachine + Clone,
+    M::Word: Hash + Eq,
+{
+    let mut count = 0;
+
+    for y in 0..size {
+        for x in 0..size {
+            if pulled(oracle, x, y)? {
+                count += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Finds the top left corner of the square closest to the emitter that
+/// fits entirely in the beam, giving up past row `max_y`. There's no such
+/// thing as a square smaller than 1, so those never fit.
+///
+/// This walks the left edge of the beam down from row `size - 1`. A square
+/// with its bottom left corner on the edge fits if its top right corner is
+/// pulled too, so each row costs about two queries.
+pub fn fit_square<M>(
+    oracle: &mut QueryOracle<M>,
+    size: i64,
+    max_y: i64,
+) -> Result<Option<(i64, i64)>, QueryError>
+where
+    M: IntcodeMachine + Clone,
+    M::Word: Hash + Eq,
+{
+    if size < 1 {
+        return Ok(None);
+    }
+
+    let mut left = 0;
+
+    for y in size - 1..=max_y {
+        let mut edge = None;
+        for x in left..left + ROW_SEARCH {
+            if pulled(oracle, x, y)? {
+                edge = Some(x);
+                break;
+            }
+        }
+
+        let x = match edge {
+            Some(x) => x,
+            None => continue,
+        };
+
+        left = x;
+
+        let top = y - (size - 1);
+        if pulled(oracle, x + size - 1, top)? {
+            return Ok(Some((x, top)));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,309 @@
+use std::{collections::VecDeque, convert::TryFrom};
+
+use crate::{
+    machine::IntcodeMachine,
+    vm::{
+        decode_instruction, Arithmetic, Error, Outcome, MODE_IMM, MODE_POS, MODE_REL, OP_ADD,
+        OP_ARB, OP_CME, OP_CML, OP_END, OP_INN, OP_JIF, OP_JIT, OP_MUL, OP_OUT,
+    },
+    word::Word,
+};
+
+/// No instruction is longer than this, so a write can only land inside
+/// instructions that start at most this many cells before it.
+const MAX_INSTRUCTION_LEN: usize = 4;
+
+/// An interpreter that decodes each instruction once and keeps the result,
+/// for tapes that spend their time running the same loops over and over.
+///
+/// Writes that land on decoded code throw away the affected entries, so
+/// self-modifying tapes still run correctly, just slower. In exchange for
+/// the speed it only runs the built-in instruction set, without hooks or
+/// custom opcodes. Use `VM` for those.
+#[derive(Default, Clone)]
+pub struct CachedVM<W> {
+    pub pc: usize,
+    pub rb: i64,
+    pub input: VecDeque<W>,
+    pub output: VecDeque<W>,
+    pub arithmetic: Arithmetic,
+    pub memory_limit: Option<usize>,
+
+    /// When set, the VM pauses with `Outcome::Paused` after running this
+    /// many more instructions, like the `Fuel` hook `VM::set_fuel` attaches.
+    pub fuel: Option<u64>,
+
+    memory: Vec<W>,
+    cache: Vec<Option<Decoded<W>>>,
+}
+
+#[derive(Clone)]
+struct Decoded<W> {
+    op: u8,
+    args: [Arg<W>; 3],
+
+    /// Where pc goes when the instruction doesn't jump.
+    next: usize,
+}
+
+/// An operand with its mode already applied. Addresses that can't be
+/// represented are kept as `None` so that the error only happens if the
+/// instruction actually runs.
+#[derive(Clone)]
+enum Arg<W> {
+    Immediate(W),
+    Position(Option<usize>),
+    Relative(Option<i64>),
+    IllegalMode(u8),
+}
+
+impl<W: Word> CachedVM<W> {
+    pub fn new(program: &[W]) -> Self {
+        let mut vm = CachedVM::default();
+        vm.load(program);
+        vm
+    }
+
+    /// Writes to memory from outside the program, like setting day 2's noun
+    /// and verb.
+    pub fn poke(&mut self, address: usize, value: W) {
+        self.write_ptr(address, value);
+    }
+
+    fn read_ptr(&self, ptr: usize) -> W {
+        self.memory.get(ptr).cloned().unwrap_or_else(|| W::from(0))
+    }
+
+    fn write_ptr(&mut self, ptr: usize, value: W) {
+        if ptr >= self.memory.len() {
+            self.memory.resize(ptr + 1, W::from(0));
+        }
+
+        self.memory[ptr] = value;
+
+        for offset in 0..MAX_INSTRUCTION_LEN {
+            if let Some(entry) = ptr
+                .checked_sub(offset)
+                .and_then(|at| self.cache.get_mut(at))
+            {
+                *entry = None;
+            }
+        }
+    }
+
+    fn decode(&self, pc: usize) -> Result<Decoded<W>, Error> {
+        let illegal = Error::IllegalInstruction { pc };
+        let inst = self.read_ptr(pc).to_i64().ok_or(illegal)?;
+        let (op, mode1, mode2, mode3) = decode_instruction(inst).ok_or(illegal)?;
+
+        let arity = match op {
+            OP_ADD | OP_MUL | OP_CML | OP_CME => 3,
+            OP_JIT | OP_JIF => 2,
+            OP_INN | OP_OUT | OP_ARB => 1,
+            OP_END => 0,
+            _ => return Err(illegal),
+        };
+
+        let arg = |index: usize, mode: u8| {
+            if index >= arity {
+                return Arg::Immediate(W::from(0));
+            }
+
+            let raw = self.read_ptr(pc + 1 + index);
+            match mode {
+                MODE_POS => Arg::Position(raw.to_address()),
+                MODE_IMM => Arg::Immediate(raw),
+                MODE_REL => Arg::Relative(raw.to_i64()),
+                _ => Arg::IllegalMode(mode),
+            }
+        };
+
+        Ok(Decoded {
+            op,
+            args: [arg(0, mode1), arg(1, mode2), arg(2, mode3)],
+            next: pc + 1 + arity,
+        })
+    }
+
+    fn invalid_address(&self) -> Error {
+        Error::InvalidAddress { pc: self.pc }
+    }
+
+    fn illegal_mode(&self, mode: u8) -> Error {
+        Error::IllegalMode { pc: self.pc, mode }
+    }
+
+    fn address(&self, arg: &Arg<W>) -> Result<usize, Error> {
+        match arg {
+            Arg::Position(ptr) => ptr.ok_or_else(|| self.invalid_address()),
+            Arg::Relative(offset) => offset
+                .and_then(|offset| self.rb.checked_add(offset))
+                .and_then(|ptr| usize::try_from(ptr).ok())
+                .ok_or_else(|| self.invalid_address()),
+            Arg::Immediate(_) => Err(self.illegal_mode(MODE_IMM)),
+            Arg::IllegalMode(mode) => Err(self.illegal_mode(*mode)),
+        }
+    }
+
+    fn value(&self, arg: &Arg<W>) -> Result<W, Error> {
+        match arg {
+            Arg::Immediate(value) => Ok(value.clone()),
+            Arg::IllegalMode(mode) => Err(self.illegal_mode(*mode)),
+            _ => Ok(self.read_ptr(self.address(arg)?)),
+        }
+    }
+
+    fn destination(&self, arg: &Arg<W>) -> Result<usize, Error> {
+        let ptr = self.address(arg)?;
+
+        match self.memory_limit {
+            Some(limit) if ptr >= limit => Err(Error::OutOfMemory { pc: self.pc }),
+            _ => Ok(ptr),
+        }
+    }
+
+    fn store(&mut self, arg: &Arg<W>, value: W) -> Result<(), Error> {
+        let ptr = self.destination(arg)?;
+        self.write_ptr(ptr, value);
+        Ok(())
+    }
+
+    fn jump(&self, dest: W, taken: bool, next: usize) -> Result<usize, Error> {
+        if taken {
+            dest.to_address().ok_or_else(|| self.invalid_address())
+        } else {
+            Ok(next)
+        }
+    }
+
+    /// Executes the instruction at pc. Returns an outcome if the VM needs to
+    /// hand control back to its caller. Errors leave pc on the instruction.
+    fn step(&mut self) -> Result<Option<Outcome>, Error> {
+        if self.cache.len() <= self.pc {
+            self.cache.resize(self.pc + 1, None);
+        }
+
+        let decoded = match &self.cache[self.pc] {
+            Some(decoded) => decoded.clone(),
+            None => {
+                let decoded = self.decode(self.pc)?;
+                self.cache[self.pc] = Some(decoded.clone());
+                decoded
+            }
+        };
+
+        let Decoded { op, args, next } = decoded;
+        let overflow = Error::Overflow { pc: self.pc };
+
+        match op {
+            OP_ADD => {
+                let a = self.value(&args[0])?;
+                let b = self.value(&args[1])?;
+                let value = a.add(&b, self.arithmetic).ok_or(overflow)?;
+                self.store(&args[2], value)?;
+            }
+            OP_MUL => {
+                let a = self.value(&args[0])?;
+                let b = self.value(&args[1])?;
+                let value = a.mul(&b, self.arithmetic).ok_or(overflow)?;
+                self.store(&args[2], value)?;
+            }
+            OP_INN => {
+                // like VM, check the destination before using up the input
+                let ptr = self.destination(&args[0])?;
+
+                match self.input.pop_front() {
+                    Some(value) => self.write_ptr(ptr, value),
+                    None => return Ok(Some(Outcome::NeedsInput)),
+                }
+            }
+            OP_OUT => {
+                let a = self.value(&args[0])?;
+                self.output.push_back(a);
+                self.pc = next;
+                return Ok(Some(Outcome::SentOutput));
+            }
+            OP_JIT => {
+                let cond = self.value(&args[0])?;
+                let dest = self.value(&args[1])?;
+                self.pc = self.jump(dest, !cond.is_zero(), next)?;
+                return Ok(None);
+            }
+            OP_JIF => {
+                let cond = self.value(&args[0])?;
+                let dest = self.value(&args[1])?;
+                self.pc = self.jump(dest, cond.is_zero(), next)?;
+                return Ok(None);
+            }
+            OP_CML => {
+                let a = self.value(&args[0])?;
+                let b = self.value(&args[1])?;
+                self.store(&args[2], W::from((a < b) as i32))?;
+            }
+            OP_CME => {
+                let a = self.value(&args[0])?;
+                let b = self.value(&args[1])?;
+                self.store(&args[2], W::from((a == b) as i32))?;
+            }
+            OP_ARB => {
+                let adjust = self.value(&args[0])?;
+                self.rb = adjust
+                    .to_i64()
+                    .and_then(|adjust| self.rb.checked_add(adjust))
+                    .ok_or_else(|| self.invalid_address())?;
+            }
+            OP_END => return Ok(Some(Outcome::Terminated)),
+            _ => unreachable!(),
+        }
+
+        self.pc = next;
+        Ok(None)
+    }
+}
+
+impl<W: Word> IntcodeMachine for CachedVM<W> {
+    type Word = W;
+
+    fn load(&mut self, program: &[W]) {
+        self.memory = program.to_vec();
+        self.cache.clear();
+        self.pc = 0;
+        self.rb = 0;
+        self.input.clear();
+        self.output.clear();
+    }
+
+    fn push_input(&mut self, value: W) {
+        self.input.push_back(value);
+    }
+
+    fn drain_output(&mut self) -> Vec<W> {
+        self.output.drain(..).collect()
+    }
+
+    fn run(&mut self) -> Outcome {
+        loop {
+            if let Some(fuel) = &mut self.fuel {
+                if *fuel == 0 {
+                    return Outcome::Paused;
+                }
+
+                *fuel -= 1;
+            }
+
+            match self.step() {
+                Ok(None) => {}
+                Ok(Some(outcome)) => return outcome,
+                Err(error) => return Outcome::Error(error),
+            }
+        }
+    }
+
+    fn memory(&self) -> &[W] {
+        &self.memory
+    }
+
+    fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+}
@@ -0,0 +1,436 @@
+//! Circuits of amplifiers like the ones from day 7, where every amplifier
+//! runs the same program with its own phase setting and feeds its output to
+//! other amplifiers.
+//!
+//! Circuits are described in a small text format:
+//!
+//! ```text
+//! # five amplifiers in a loop
+//! nodes A B C D E
+//! A -> B
+//! B -> C
+//! C -> D
+//! D -> E
+//! E -> A
+//! input A 0
+//! output E
+//! ```
+//!
+//! `nodes` lists the amplifiers, which get their phases in that order. An
+//! edge can fan out to several nodes at once (`A -> B C`) and a node can
+//! have several edges coming in, in which case its inputs arrive in the
+//! order they were sent. `input` queues values after a node's phase, and
+//! the answer is the last thing the `output` node sends.
+
+use std::{
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    hooks::Fuel,
+    machine::IntcodeMachine,
+    oracle::{QueryError, QueryOracle},
+    vm::{Error, Outcome, VM},
+    word::Word,
+};
+
+/// How many values can be sent around a circuit before it's assumed to be
+/// running forever.
+pub const MESSAGE_LIMIT: usize = 1_000_000;
+
+/// How many instructions one node can run before it's assumed to be stuck
+/// in a loop.
+pub const STEP_LIMIT: u64 = 10_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    Parse {
+        line: usize,
+        message: String,
+    },
+    UnknownNode {
+        line: usize,
+        name: String,
+    },
+
+    /// The circuit has a different number of nodes than phases were given.
+    PhaseCount {
+        expected: usize,
+        found: usize,
+    },
+
+    /// An `input` line has a value that doesn't fit in the machine's words.
+    InputTooLarge {
+        node: String,
+        value: i64,
+    },
+
+    Fault {
+        node: String,
+        error: Error,
+    },
+
+    /// Every node still running is waiting on input that will never come.
+    Deadlock {
+        waiting: Vec<String>,
+    },
+
+    /// The circuit sent `MESSAGE_LIMIT` values, or a node ran `STEP_LIMIT`
+    /// instructions, without stopping.
+    Runaway,
+
+    /// `solve_chain` was given a circuit that isn't a chain.
+    NotAChain,
+
+    /// Everything stopped without the output node sending anything.
+    NoOutput,
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitError::Parse { line, message } => {
+                write!(formatter, "line {}: {}", line, message)
+            }
+            CircuitError::UnknownNode { line, name } => {
+                write!(formatter, "line {}: no node named {}", line, name)
+            }
+            CircuitError::PhaseCount { expected, found } => write!(
+                formatter,
+                "circuit has {} nodes but {} phases were given",
+                expected, found
+            ),
+            CircuitError::InputTooLarge { node, value } => {
+                write!(formatter, "input {} for node {} is too large", value, node)
+            }
+            CircuitError::Fault { node, error } => {
+                write!(formatter, "node {} faulted: {:?}", node, error)
+            }
+            CircuitError::Deadlock { waiting } => {
+                write!(formatter, "deadlocked waiting on {}", waiting.join(", "))
+            }
+            CircuitError::Runaway => write!(formatter, "circuit never stopped"),
+            CircuitError::NotAChain => write!(formatter, "circuit isn't a chain"),
+            CircuitError::NoOutput => write!(formatter, "output node never sent anything"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Circuit {
+    pub nodes: Vec<String>,
+
+    /// Which nodes each node's output goes to, by index.
+    pub edges: Vec<Vec<usize>>,
+
+    /// Values queued for each node after its phase.
+    pub inputs: Vec<Vec<i64>>,
+
+    pub output: usize,
+}
+
+/// A through Z, then N26, N27 and so on.
+fn node_names(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|index| match index {
+            0..=25 => ((b'A' + index as u8) as char).to_string(),
+            _ => format!("N{}", index),
+        })
+        .collect()
+}
+
+impl Circuit {
+    /// Amplifiers in a line, the first starting on 0, like part one.
+    pub fn chain(count: usize) -> Circuit {
+        let mut edges = vec![Vec::new(); count];
+        for index in 1..count {
+            edges[index - 1].push(index);
+        }
+
+        let mut inputs = vec![Vec::new(); count];
+        if let Some(first) = inputs.first_mut() {
+            first.push(0);
+        }
+
+        Circuit {
+            nodes: node_names(count),
+            edges,
+            inputs,
+            output: count.saturating_sub(1),
+        }
+    }
+
+    /// Amplifiers in a loop, like part two.
+    pub fn ring(count: usize) -> Circuit {
+        let mut circuit = Circuit::chain(count);
+
+        if let Some(last) = circuit.edges.last_mut() {
+            last.push(0);
+        }
+
+        circuit
+    }
+
+    fn find(&self, line: usize, name: &str) -> Result<usize, CircuitError> {
+        self.nodes
+            .iter()
+            .position(|node| node == name)
+            .ok_or_else(|| CircuitError::UnknownNode {
+                line,
+                name: name.to_owned(),
+            })
+    }
+
+    pub fn parse(text: &str) -> Result<Circuit, CircuitError> {
+        let mut circuit = Circuit::default();
+        let mut output = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap().trim();
+            let error = |message: &str| CircuitError::Parse {
+                line: line_number,
+                message: message.to_owned(),
+            };
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {}
+                ["nodes", names @ ..] => {
+                    if !circuit.nodes.is_empty() {
+                        return Err(error("nodes listed twice"));
+                    }
+
+                    circuit.nodes = names.iter().map(|name| name.to_string()).collect();
+                    circuit.edges = vec![Vec::new(); names.len()];
+                    circuit.inputs = vec![Vec::new(); names.len()];
+                }
+                ["input", name, values @ ..] => {
+                    let node = circuit.find(line_number, name)?;
+
+                    for value in values {
+                        let value = value.parse().map_err(|_| error("inputs must be numbers"))?;
+                        circuit.inputs[node].push(value);
+                    }
+                }
+                ["output", name] => output = Some(circuit.find(line_number, name)?),
+                [from, "->", targets @ ..] if !targets.is_empty() => {
+                    let from = circuit.find(line_number, from)?;
+
+                    for target in targets {
+                        let target = circuit.find(line_number, target)?;
+                        circuit.edges[from].push(target);
+                    }
+                }
+                _ => return Err(error("expected nodes, input, output or an edge")),
+            }
+        }
+
+        circuit.output = output.ok_or(CircuitError::Parse {
+            line: text.lines().count(),
+            message: "no output node".to_owned(),
+        })?;
+
+        Ok(circuit)
+    }
+
+    /// The queue a node starts with: its phase, then its `input` values.
+    fn initial_inputs<W: Word>(&self, node: usize, phase: &W) -> Result<Vec<W>, CircuitError> {
+        let mut queue = vec![phase.clone()];
+
+        for &value in &self.inputs[node] {
+            queue.push(
+                W::from_i64(value).ok_or_else(|| CircuitError::InputTooLarge {
+                    node: self.nodes[node].clone(),
+                    value,
+                })?,
+            );
+        }
+
+        Ok(queue)
+    }
+
+    fn check_phases<W>(&self, phases: &[W]) -> Result<(), CircuitError> {
+        if phases.len() == self.nodes.len() {
+            Ok(())
+        } else {
+            Err(CircuitError::PhaseCount {
+                expected: self.nodes.len(),
+                found: phases.len(),
+            })
+        }
+    }
+
+    /// Runs every node on a fresh copy of `program` with its phase, passing
+    /// values along edges until everything stops. Returns the last value the
+    /// output node sent.
+    ///
+    /// Nodes take turns in order, each running until it needs input it
+    /// doesn't have. A whole round where no node could run means the
+    /// circuit is stuck, and a node that runs `STEP_LIMIT` instructions or
+    /// a circuit that sends `MESSAGE_LIMIT` values is assumed to never stop.
+    pub fn solve<W: Word>(&self, program: &[W], phases: &[W]) -> Result<W, CircuitError> {
+        self.check_phases(phases)?;
+
+        let mut machines: Vec<VM<W>> = Vec::with_capacity(phases.len());
+
+        for (node, phase) in phases.iter().enumerate() {
+            let mut machine = VM::default();
+            machine.load(program);
+            machine.set_hook(Arc::new(Mutex::new(Fuel {
+                remaining: STEP_LIMIT,
+            })));
+
+            for input in self.initial_inputs(node, phase)? {
+                machine.push_input(input);
+            }
+
+            machines.push(machine);
+        }
+
+        // a node is ready until it blocks on input, and becomes ready again
+        // once some arrives
+        let mut ready = vec![true; machines.len()];
+        let mut stopped = vec![false; machines.len()];
+        let mut answer = None;
+        let mut sent = 0;
+
+        loop {
+            let mut ran = false;
+
+            for node in 0..machines.len() {
+                if stopped[node] || !ready[node] {
+                    continue;
+                }
+
+                ran = true;
+
+                loop {
+                    let outcome = machines[node].run();
+
+                    // deliver as we go, so a node that never stops talking
+                    // still runs into the message limit
+                    for value in machines[node].drain_output() {
+                        sent += 1;
+                        if sent > MESSAGE_LIMIT {
+                            return Err(CircuitError::Runaway);
+                        }
+
+                        for &target in &self.edges[node] {
+                            machines[target].push_input(value.clone());
+                            ready[target] = true;
+                        }
+
+                        if node == self.output {
+                            answer = Some(value);
+                        }
+                    }
+
+                    match outcome {
+                        Outcome::SentOutput => {}
+                        // only the fuel hook pauses these machines
+                        Outcome::Paused => return Err(CircuitError::Runaway),
+                        Outcome::NeedsInput => {
+                            ready[node] = false;
+                            break;
+                        }
+                        Outcome::Terminated => {
+                            stopped[node] = true;
+                            break;
+                        }
+                        Outcome::Error(error) => {
+                            return Err(CircuitError::Fault {
+                                node: self.nodes[node].clone(),
+                                error,
+                            })
+                        }
+                    }
+                }
+            }
+
+            if stopped.iter().all(|&stopped| stopped) {
+                return answer.ok_or(CircuitError::NoOutput);
+            }
+
+            if !ran {
+                let waiting = (0..machines.len())
+                    .filter(|&node| !stopped[node])
+                    .map(|node| self.nodes[node].clone())
+                    .collect();
+
+                return Err(CircuitError::Deadlock { waiting });
+            }
+        }
+    }
+
+    /// The nodes in order if the circuit is a plain chain ending at the
+    /// output, where each node only hears from the one before it.
+    pub fn chain_order(&self) -> Option<Vec<usize>> {
+        let mut heard_from = vec![None; self.nodes.len()];
+
+        for (from, targets) in self.edges.iter().enumerate() {
+            match targets.as_slice() {
+                [] => {}
+                &[to] if heard_from[to].is_none() => heard_from[to] = Some(from),
+                _ => return None,
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut next = heard_from.iter().position(Option::is_none);
+
+        while let Some(node) = next {
+            if order.len() == self.nodes.len() {
+                return None;
+            }
+
+            order.push(node);
+            next = self.edges[node].first().copied();
+        }
+
+        (order.len() == self.nodes.len() && order.last() == Some(&self.output)).then_some(order)
+    }
+
+    /// Solves a chain one node at a time through `oracle`. Each node in a
+    /// chain gets all of its input before it starts, so nodes that see the
+    /// same phase and input again are answered from memory.
+    pub fn solve_chain<M>(
+        &self,
+        oracle: &mut QueryOracle<M>,
+        phases: &[M::Word],
+    ) -> Result<M::Word, CircuitError>
+    where
+        M: IntcodeMachine + Clone,
+        M::Word: Hash + Eq,
+    {
+        self.check_phases(phases)?;
+        let order = self.chain_order().ok_or(CircuitError::NotAChain)?;
+
+        let mut outputs = Vec::new();
+
+        for node in order {
+            let mut inputs = self.initial_inputs(node, &phases[node])?;
+            inputs.append(&mut outputs);
+
+            outputs = oracle.query(&inputs).map_err(|error| match error {
+                QueryError::Fault(error) => CircuitError::Fault {
+                    node: self.nodes[node].clone(),
+                    error,
+                },
+                QueryError::Starved => CircuitError::Deadlock {
+                    waiting: vec![self.nodes[node].clone()],
+                },
+                QueryError::Paused => CircuitError::Runaway,
+                QueryError::InputTooLarge(value) => CircuitError::InputTooLarge {
+                    node: self.nodes[node].clone(),
+                    value,
+                },
+            })?;
+        }
+
+        outputs.last().cloned().ok_or(CircuitError::NoOutput)
+    }
+}
@@ -0,0 +1,518 @@
+//! A table of small programs with known behaviour, for checking that an
+//! Intcode implementation agrees with the puzzle statements.
+//!
+//! Every case is a program, its input and what should come out: the
+//! outputs, how the run stops and, optionally, some cells of the final
+//! memory. Cases include the examples from days 2, 5 and 9, every opcode in
+//! every parameter mode, reads past the end of memory, relative base
+//! arithmetic and halting behaviour.
+
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{machine::IntcodeMachine, vm::Outcome, word::Word};
+
+/// How a case is expected to stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Halted,
+    NeedsInput,
+    Fault,
+}
+
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub name: String,
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+    pub outputs: Vec<i64>,
+    pub end: End,
+    pub memory: Vec<(usize, i64)>,
+}
+
+impl Case {
+    fn new(name: impl Into<String>, program: &[i64], input: &[i64], outputs: &[i64]) -> Self {
+        Case {
+            name: name.into(),
+            program: program.to_vec(),
+            input: input.to_vec(),
+            outputs: outputs.to_vec(),
+            end: End::Halted,
+            memory: Vec::new(),
+        }
+    }
+
+    fn ending(mut self, end: End) -> Self {
+        self.end = end;
+        self
+    }
+
+    fn with_memory(mut self, memory: &[(usize, i64)]) -> Self {
+        self.memory = memory.to_vec();
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub case: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {}", self.case, self.reason)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    pub skipped: Vec<String>,
+    pub failures: Vec<Failure>,
+}
+
+/// Outputs beyond this many mean the program is probably stuck in a loop.
+const MAX_EVENTS: usize = 1000;
+
+/// Runs every case against a fresh machine from `make`. Faults have to come
+/// back as `Outcome::Error`, so a machine that panics fails the case, and
+/// cases with values too wide for the machine's word are skipped.
+pub fn check<M: IntcodeMachine>(make: impl Fn() -> M) -> Report {
+    let mut report = Report::default();
+
+    for case in cases() {
+        let mut machine = make();
+
+        let values = case
+            .program
+            .iter()
+            .chain(&case.input)
+            .chain(&case.outputs)
+            .chain(case.memory.iter().map(|(_, value)| value));
+
+        if !values
+            .into_iter()
+            .all(|&value| M::Word::from_i64(value).is_some())
+        {
+            report.skipped.push(case.name);
+            continue;
+        }
+
+        let program: Vec<M::Word> = case
+            .program
+            .iter()
+            .map(|&value| M::Word::from_i64(value).unwrap())
+            .collect();
+        machine.load(&program);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run_case(&mut machine, &case)));
+        let result = result.unwrap_or_else(|_| Err("panicked".to_owned()));
+
+        match result {
+            Ok(()) => report.passed += 1,
+            Err(reason) => report.failures.push(Failure {
+                case: case.name,
+                reason,
+            }),
+        }
+    }
+
+    report
+}
+
+fn run_case<M: IntcodeMachine>(machine: &mut M, case: &Case) -> Result<(), String> {
+    for &value in &case.input {
+        machine.push_input(M::Word::from_i64(value).unwrap());
+    }
+
+    let mut outputs = Vec::new();
+    let mut end = None;
+
+    for _ in 0..MAX_EVENTS {
+        match machine.run() {
+            Outcome::SentOutput => {
+                for value in machine.drain_output() {
+                    let value = value.to_i64().ok_or("output doesn't fit in an i64")?;
+                    outputs.push(value);
+                }
+            }
+            Outcome::Terminated => {
+                end = Some(End::Halted);
+                break;
+            }
+            Outcome::NeedsInput => {
+                end = Some(End::NeedsInput);
+                break;
+            }
+            // nothing in the suite asks a machine to pause, so treat it like
+            // any other unexpected stop
+            Outcome::Error(_) | Outcome::Paused => {
+                end = Some(End::Fault);
+                break;
+            }
+        }
+    }
+
+    if outputs != case.outputs {
+        return Err(format!(
+            "expected outputs {:?}, got {:?}",
+            case.outputs, outputs
+        ));
+    }
+
+    match end {
+        Some(end) if end == case.end => {}
+        Some(end) => return Err(format!("expected {:?}, stopped with {:?}", case.end, end)),
+        None => return Err(format!("still running after {} outputs", MAX_EVENTS)),
+    }
+
+    // a halted program has to stay halted when it's resumed, and a faulted
+    // one has to stay on the instruction that faulted
+    if case.end == End::Halted && !matches!(machine.run(), Outcome::Terminated) {
+        return Err("didn't stay halted".to_owned());
+    }
+
+    if case.end == End::Fault && !matches!(machine.run(), Outcome::Error(_)) {
+        return Err("didn't fault again when resumed".to_owned());
+    }
+
+    for &(address, expected) in &case.memory {
+        let actual = machine
+            .memory()
+            .get(address)
+            .and_then(|value| value.to_i64());
+        if actual != Some(expected) {
+            return Err(format!(
+                "expected {} at address {}, found {:?}",
+                expected, address, actual
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+const OP_NAMES: &[(i64, &str)] = &[
+    (1, "add"),
+    (2, "mul"),
+    (3, "in"),
+    (4, "out"),
+    (5, "jump-if-true"),
+    (6, "jump-if-false"),
+    (7, "less-than"),
+    (8, "equals"),
+    (9, "adjust-rb"),
+];
+
+const MODE_NAMES: &[&str] = &["position", "immediate", "relative"];
+
+fn op_name(op: i64) -> &'static str {
+    OP_NAMES.iter().find(|(code, _)| *code == op).unwrap().1
+}
+
+/// Every case in the suite.
+pub fn cases() -> Vec<Case> {
+    let mut cases = puzzle_examples();
+    cases.extend(binary_op_modes());
+    cases.extend(jump_modes());
+    cases.extend(io_modes());
+    cases.extend(edge_cases());
+    cases
+}
+
+fn puzzle_examples() -> Vec<Case> {
+    let larger_compare = &[
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+    let quine = &[
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+
+    vec![
+        Case::new("day 2: 1,0,0,0,99", &[1, 0, 0, 0, 99], &[], &[]).with_memory(&[(0, 2)]),
+        Case::new("day 2: 2,3,0,3,99", &[2, 3, 0, 3, 99], &[], &[]).with_memory(&[(3, 6)]),
+        Case::new("day 2: 2,4,4,5,99,0", &[2, 4, 4, 5, 99, 0], &[], &[]).with_memory(&[(5, 9801)]),
+        Case::new(
+            "day 2: 1,1,1,4,99,5,6,0,99",
+            &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[],
+            &[],
+        )
+        .with_memory(&[(0, 30), (4, 2)]),
+        Case::new(
+            "day 2: 1,9,10,3,...",
+            &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[],
+            &[],
+        )
+        .with_memory(&[(0, 3500), (3, 70)]),
+        Case::new("day 5: echo", &[3, 0, 4, 0, 99], &[42], &[42]),
+        Case::new("day 5: 1002,4,3,4,33", &[1002, 4, 3, 4, 33], &[], &[]).with_memory(&[(4, 99)]),
+        Case::new("day 5: 1101,100,-1,4,0", &[1101, 100, -1, 4, 0], &[], &[])
+            .with_memory(&[(4, 99)]),
+        Case::new(
+            "day 5: equal to 8, position mode",
+            &[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8],
+            &[8],
+            &[1],
+        ),
+        Case::new(
+            "day 5: not equal to 8, position mode",
+            &[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8],
+            &[7],
+            &[0],
+        ),
+        Case::new(
+            "day 5: less than 8, position mode",
+            &[3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8],
+            &[5],
+            &[1],
+        ),
+        Case::new(
+            "day 5: equal to 8, immediate mode",
+            &[3, 3, 1108, -1, 8, 3, 4, 3, 99],
+            &[8],
+            &[1],
+        ),
+        Case::new(
+            "day 5: not less than 8, immediate mode",
+            &[3, 3, 1107, -1, 8, 3, 4, 3, 99],
+            &[9],
+            &[0],
+        ),
+        Case::new(
+            "day 5: jump on zero, position mode",
+            &[3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+            &[0],
+            &[0],
+        ),
+        Case::new(
+            "day 5: jump on nonzero, immediate mode",
+            &[3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1],
+            &[5],
+            &[1],
+        ),
+        Case::new("day 5: compare to 8, below", larger_compare, &[7], &[999]),
+        Case::new("day 5: compare to 8, equal", larger_compare, &[8], &[1000]),
+        Case::new("day 5: compare to 8, above", larger_compare, &[9], &[1001]),
+        Case::new("day 9: quine", quine, &[], quine),
+        Case::new(
+            "day 9: 16 digit output",
+            &[1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0],
+            &[],
+            &[1_219_070_632_396_864],
+        ),
+        Case::new(
+            "day 9: large number output",
+            &[104, 1_125_899_906_842_624, 99],
+            &[],
+            &[1_125_899_906_842_624],
+        ),
+    ]
+}
+
+/// Where operands live in the generated cases. The relative base is set to
+/// `BASE` first, so relative operands point at the same cells as positional
+/// ones.
+const BASE: i64 = 10;
+const LEFT: i64 = 20;
+const RIGHT: i64 = 21;
+const RESULT: i64 = 22;
+
+fn operand(mode: i64, address: i64, value: i64) -> i64 {
+    match mode {
+        0 => address,
+        1 => value,
+        _ => address - BASE,
+    }
+}
+
+/// Lays out `code` after a relative base adjustment, padded so that `data`
+/// lands at `LEFT`.
+fn with_data(code: &[i64], data: &[i64]) -> Vec<i64> {
+    let mut program = vec![109, BASE];
+    program.extend_from_slice(code);
+    assert!(program.len() <= LEFT as usize);
+    program.resize(LEFT as usize, 0);
+    program.extend_from_slice(data);
+    program
+}
+
+fn binary_op_modes() -> Vec<Case> {
+    let (left, right) = (6, 7);
+    let ops: &[(i64, i64)] = &[(1, 13), (2, 42), (7, 1), (8, 0)];
+
+    let mut cases = Vec::new();
+
+    for &(op, expected) in ops {
+        for mode1 in 0..3 {
+            for mode2 in 0..3 {
+                for &mode3 in &[0, 2] {
+                    let instruction = op + 100 * mode1 + 1000 * mode2 + 10000 * mode3;
+                    let out = operand(mode3, RESULT, 0);
+                    let code = [
+                        instruction,
+                        operand(mode1, LEFT, left),
+                        operand(mode2, RIGHT, right),
+                        out,
+                        4 + 100 * mode3,
+                        out,
+                        99,
+                    ];
+
+                    cases.push(Case::new(
+                        format!(
+                            "{} ({}, {}, {})",
+                            op_name(op),
+                            MODE_NAMES[mode1 as usize],
+                            MODE_NAMES[mode2 as usize],
+                            MODE_NAMES[mode3 as usize]
+                        ),
+                        &with_data(&code, &[left, right, 0]),
+                        &[],
+                        &[expected],
+                    ));
+                }
+            }
+        }
+    }
+
+    cases
+}
+
+fn jump_modes() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    for &(op, cond) in &[(5, 3), (6, 0)] {
+        for mode1 in 0..3 {
+            for mode2 in 0..3 {
+                // taken jumps land on the second output
+                let target = 12;
+                let code = [
+                    op + 100 * mode1 + 1000 * mode2,
+                    operand(mode1, LEFT, cond),
+                    operand(mode2, RIGHT, target),
+                    104,
+                    0,
+                    99,
+                    0,
+                    0,
+                    0,
+                    0,
+                    104,
+                    1,
+                    99,
+                ];
+
+                cases.push(Case::new(
+                    format!(
+                        "{} ({}, {})",
+                        op_name(op),
+                        MODE_NAMES[mode1 as usize],
+                        MODE_NAMES[mode2 as usize]
+                    ),
+                    &with_data(&code, &[cond, target]),
+                    &[],
+                    &[1],
+                ));
+            }
+        }
+    }
+
+    cases
+}
+
+fn io_modes() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    for &mode in &[0, 2] {
+        let out = operand(mode, RESULT, 0);
+        cases.push(Case::new(
+            format!("{} ({})", op_name(3), MODE_NAMES[mode as usize]),
+            &with_data(&[3 + 100 * mode, out, 4 + 100 * mode, out, 99], &[0, 0, 0]),
+            &[17],
+            &[17],
+        ));
+    }
+
+    for mode in 0..3 {
+        cases.push(Case::new(
+            format!("{} ({})", op_name(4), MODE_NAMES[mode as usize]),
+            &with_data(&[4 + 100 * mode, operand(mode, LEFT, 23), 99], &[23]),
+            &[],
+            &[23],
+        ));
+    }
+
+    for mode in 0..3 {
+        // moves the relative base from BASE to LEFT
+        let adjust = LEFT - BASE;
+        cases.push(Case::new(
+            format!("{} ({})", op_name(9), MODE_NAMES[mode as usize]),
+            &with_data(
+                &[9 + 100 * mode, operand(mode, RIGHT, adjust), 204, 0, 99],
+                &[31, adjust],
+            ),
+            &[],
+            &[31],
+        ));
+    }
+
+    cases
+}
+
+fn edge_cases() -> Vec<Case> {
+    vec![
+        Case::new("halt immediately", &[99], &[], &[]),
+        Case::new("read past end of memory", &[4, 100, 99], &[], &[0]),
+        Case::new(
+            "relative read past end of memory",
+            &[204, 50, 99],
+            &[],
+            &[0],
+        ),
+        Case::new(
+            "write past end of memory",
+            &[1101, 2, 3, 50, 4, 50, 99],
+            &[],
+            &[5],
+        )
+        .with_memory(&[(50, 5)]),
+        Case::new(
+            "negative relative base adjustment",
+            &[109, 20, 109, -15, 204, 3, 99, 0, 42],
+            &[],
+            &[42],
+        ),
+        Case::new(
+            "relative base accumulates",
+            &[109, 3, 109, 3, 204, 1, 99, 7],
+            &[],
+            &[7],
+        ),
+        Case::new(
+            "relative input",
+            &[109, 5, 203, 2, 204, 2, 99, 0],
+            &[-4],
+            &[-4],
+        ),
+        Case::new("starved for input", &[104, 1, 3, 0, 99], &[], &[1]).ending(End::NeedsInput),
+        Case::new("illegal instruction", &[98], &[], &[]).ending(End::Fault),
+        Case::new("illegal instruction after output", &[104, 7, 98], &[], &[7]).ending(End::Fault),
+        Case::new("illegal operand mode", &[304, 0, 99], &[], &[]).ending(End::Fault),
+        Case::new(
+            "store through an immediate",
+            &[11101, 1, 1, 5, 99, 0],
+            &[],
+            &[],
+        )
+        .ending(End::Fault)
+        .with_memory(&[(5, 0)]),
+        Case::new("negative address", &[4, -1, 99], &[], &[]).ending(End::Fault),
+    ]
+}
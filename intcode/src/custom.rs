@@ -0,0 +1,67 @@
+use std::{fmt, sync::Arc};
+
+use crate::vm::{Outcome, VM};
+
+type Handler<W> = dyn Fn(&mut VM<W>, &mut [W]) -> Option<Outcome> + Send + Sync;
+
+/// An instruction registered with `VM::register_opcode`, for experimenting
+/// with extended Intcode dialects.
+///
+/// Operands are decoded like the built-in instructions: each one takes its
+/// mode from the instruction's digits, and operands listed in `writes` are
+/// treated as pointers. The handler gets the loaded value of every read
+/// operand and the current value behind every write operand. Whatever it
+/// leaves in a write operand's slot is stored back once it returns.
+///
+/// Returning `None` moves on to the next instruction. Returning an outcome
+/// hands control back to the caller of `run_partial`, the same way the
+/// built-in instructions do. `NeedsInput`, `Terminated` and `Error` leave pc
+/// on this instruction and skip the stores, so it runs again when resumed.
+pub struct CustomOp<W> {
+    pub name: &'static str,
+    pub arity: usize,
+    pub writes: Vec<usize>,
+    pub handler: Arc<Handler<W>>,
+}
+
+impl<W> CustomOp<W> {
+    pub fn new<F>(name: &'static str, arity: usize, writes: &[usize], handler: F) -> Self
+    where
+        F: Fn(&mut VM<W>, &mut [W]) -> Option<Outcome> + Send + Sync + 'static,
+    {
+        assert!(
+            writes.iter().all(|&index| index < arity),
+            "write operand out of range for {}",
+            name
+        );
+
+        CustomOp {
+            name,
+            arity,
+            writes: writes.to_vec(),
+            handler: Arc::new(handler),
+        }
+    }
+}
+
+impl<W> Clone for CustomOp<W> {
+    fn clone(&self) -> Self {
+        CustomOp {
+            name: self.name,
+            arity: self.arity,
+            writes: self.writes.clone(),
+            handler: Arc::clone(&self.handler),
+        }
+    }
+}
+
+impl<W> fmt::Debug for CustomOp<W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("CustomOp")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("writes", &self.writes)
+            .finish()
+    }
+}
@@ -0,0 +1,161 @@
+//! Comparing memory between two points in a program's life, to see what it
+//! computed and which cells it uses as variables.
+//!
+//! Memory is compared cell by cell, with cells past the end of the shorter
+//! side counting as 0 like they do for the VM.
+
+use std::{fmt::Write, ops::Range};
+
+use crate::{disasm, vm::VM, word::Word};
+
+/// A run of neighbouring cells that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<W> {
+    pub start: usize,
+    pub before: Vec<W>,
+    pub after: Vec<W>,
+}
+
+impl<W> Change<W> {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.after.len()
+    }
+}
+
+fn cell<W: Word>(memory: &[W], address: usize) -> W {
+    memory.get(address).cloned().unwrap_or_else(|| W::from(0))
+}
+
+pub fn diff<W: Word>(before: &[W], after: &[W]) -> Vec<Change<W>> {
+    let mut changes: Vec<Change<W>> = Vec::new();
+
+    for address in 0..before.len().max(after.len()) {
+        let old = cell(before, address);
+        let new = cell(after, address);
+
+        if old == new {
+            continue;
+        }
+
+        match changes.last_mut() {
+            Some(change) if change.range().end == address => {
+                change.before.push(old);
+                change.after.push(new);
+            }
+            _ => changes.push(Change {
+                start: address,
+                before: vec![old],
+                after: vec![new],
+            }),
+        }
+    }
+
+    changes
+}
+
+fn ascii<W: Word>(value: &W) -> String {
+    match value.to_i64() {
+        Some(10) => "'\\n'".to_owned(),
+        Some(code @ 32..=126) => format!("'{}'", code as u8 as char),
+        _ => String::new(),
+    }
+}
+
+fn instruction<W: Word>(memory: &[W], address: usize) -> String {
+    disasm::disassemble(memory, address)
+        .map(|instruction| instruction.to_string())
+        .unwrap_or_default()
+}
+
+/// Lists every change as a table of the values before and after, the new
+/// value as ASCII if it's printable, and the instruction that decodes from
+/// each cell afterwards, if any.
+pub fn report<W: Word>(before: &[W], after: &[W]) -> String {
+    let changes = diff(before, after);
+    let mut report = String::new();
+
+    if changes.is_empty() {
+        return "No cells changed\n".to_owned();
+    }
+
+    for change in &changes {
+        let range = change.range();
+        writeln!(
+            report,
+            "{}..{} ({} cells)",
+            range.start,
+            range.end,
+            range.len()
+        )
+        .unwrap();
+        writeln!(
+            report,
+            "  {:>8}  {:>16}  {:>16}  {:<5}  instruction",
+            "address", "before", "after", "ascii"
+        )
+        .unwrap();
+
+        for (offset, (old, new)) in change.before.iter().zip(&change.after).enumerate() {
+            let address = change.start + offset;
+
+            let row = format!(
+                "  {:>8}  {:>16}  {:>16}  {:<5}  {}",
+                address,
+                old,
+                new,
+                ascii(new),
+                instruction(after, address)
+            );
+
+            writeln!(report, "{}", row.trim_end()).unwrap();
+        }
+
+        report.push('\n');
+    }
+
+    report
+}
+
+/// Like `report`, but also says where pc and the relative base moved.
+pub fn report_vms<W: Word>(before: &VM<W>, after: &VM<W>) -> String {
+    let mut registers = String::new();
+
+    if before.pc != after.pc {
+        writeln!(registers, "pc: {} -> {}", before.pc, after.pc).unwrap();
+    }
+
+    if before.rb != after.rb {
+        writeln!(registers, "rb: {} -> {}", before.rb, after.rb).unwrap();
+    }
+
+    registers + &report(&before.memory, &after.memory)
+}
+
+/// Shows a range of memory in the same format as `report`, for looking at
+/// cells that didn't change.
+pub fn inspect<W: Word>(memory: &[W], range: Range<usize>) -> String {
+    let mut report = String::new();
+
+    writeln!(
+        report,
+        "  {:>8}  {:>16}  {:<5}  instruction",
+        "address", "value", "ascii"
+    )
+    .unwrap();
+
+    for address in range {
+        let value = cell(memory, address);
+
+        let row = format!(
+            "  {:>8}  {:>16}  {:<5}  {}",
+            address,
+            value,
+            ascii(&value),
+            instruction(memory, address)
+        );
+
+        writeln!(report, "{}", row.trim_end()).unwrap();
+    }
+
+    report
+}
@@ -0,0 +1,115 @@
+//! Turns memory back into the assembly syntax understood by `asm`.
+
+use std::fmt;
+
+use crate::{
+    asm::INSTRUCTIONS,
+    vm::{decode_instruction, MODE_IMM, MODE_POS, MODE_REL},
+    word::Word,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand<W> {
+    Position(W),
+    Immediate(W),
+    Relative(W),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction<W> {
+    pub address: usize,
+    pub mnemonic: &'static str,
+    pub operands: Vec<Operand<W>>,
+}
+
+impl<W> Instruction<W> {
+    /// The address just past the instruction, where the next one starts.
+    pub fn end(&self) -> usize {
+        self.address + self.operands.len() + 1
+    }
+}
+
+impl<W: Word> fmt::Display for Operand<W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Position(value) => write!(formatter, "[{}]", value),
+            Operand::Immediate(value) => write!(formatter, "{}", value),
+            Operand::Relative(value) if value.is_zero() => write!(formatter, "[rb]"),
+            Operand::Relative(value) if *value < W::from(0) => write!(formatter, "[rb{}]", value),
+            Operand::Relative(value) => write!(formatter, "[rb+{}]", value),
+        }
+    }
+}
+
+impl<W: Word> fmt::Display for Instruction<W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.mnemonic)?;
+
+        for (index, operand) in self.operands.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(formatter, "{}{}", separator, operand)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the instruction at `address`. Returns `None` if the cell doesn't
+/// hold one the VM could run, which usually means it's data.
+pub fn disassemble<W: Word>(memory: &[W], address: usize) -> Option<Instruction<W>> {
+    let raw = memory.get(address)?.to_i64()?;
+    let (op, mode1, mode2, mode3) = decode_instruction(raw)?;
+    let &(mnemonic, _, arity, writes) = INSTRUCTIONS.iter().find(|(_, code, ..)| *code == op)?;
+    let modes = [mode1, mode2, mode3];
+
+    // digits past the last operand's mode would be ignored by the VM, but
+    // they don't show up in real code
+    if raw / 100 >= 10_i64.pow(arity as u32) {
+        return None;
+    }
+
+    let mut operands = Vec::with_capacity(arity);
+
+    for (index, &mode) in modes.iter().enumerate().take(arity) {
+        let value = memory
+            .get(address + 1 + index)
+            .cloned()
+            .unwrap_or_else(|| W::from(0));
+
+        operands.push(match mode {
+            MODE_POS => Operand::Position(value),
+            MODE_IMM if !writes.contains(&index) => Operand::Immediate(value),
+            MODE_REL => Operand::Relative(value),
+            _ => return None,
+        });
+    }
+
+    Some(Instruction {
+        address,
+        mnemonic,
+        operands,
+    })
+}
+
+/// Disassembles memory from `start` onwards, following on from each
+/// instruction to the next. Cells that aren't instructions come out as
+/// `data`, one at a time.
+pub fn listing<W: Word>(memory: &[W], start: usize, count: usize) -> Vec<(usize, String)> {
+    let mut lines = Vec::with_capacity(count);
+    let mut address = start;
+
+    while lines.len() < count && address < memory.len() {
+        match disassemble(memory, address) {
+            Some(instruction) => {
+                lines.push((address, instruction.to_string()));
+                address = instruction.end();
+            }
+            None => {
+                lines.push((address, format!("data {}", memory[address])));
+                address += 1;
+            }
+        }
+    }
+
+    lines
+}
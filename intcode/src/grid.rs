@@ -0,0 +1,40 @@
+//! Positions on the flat grids that the robots, droids and games from the
+//! later days move around. Positions are `(x, y)` with y growing downwards,
+//! so that drawing a grid row by row puts the smallest y at the top.
+
+use std::collections::HashMap;
+
+/// The smallest rectangle holding every position, as its top left and bottom
+/// right corners, or `None` if there aren't any.
+pub fn bounds(positions: impl IntoIterator<Item = (i64, i64)>) -> Option<((i64, i64), (i64, i64))> {
+    positions
+        .into_iter()
+        .fold(None, |bounds, (x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some(((left, top), (right, bottom))) => {
+                Some(((left.min(x), top.min(y)), (right.max(x), bottom.max(y))))
+            }
+        })
+}
+
+/// Draws every position in the bounds of `cells` as one character, a line
+/// per row. `glyph` is also asked about positions inside the bounds that
+/// aren't in `cells`, with `None`.
+pub fn render<T>(
+    cells: &HashMap<(i64, i64), T>,
+    mut glyph: impl FnMut((i64, i64), Option<&T>) -> char,
+) -> String {
+    let mut output = String::new();
+
+    if let Some(((left, top), (right, bottom))) = bounds(cells.keys().copied()) {
+        for y in top..=bottom {
+            for x in left..=right {
+                output.push(glyph((x, y), cells.get(&(x, y))));
+            }
+
+            output.push('\n');
+        }
+    }
+
+    output
+}
@@ -0,0 +1,112 @@
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use crate::vm::VM;
+
+/// Callbacks into a running VM, attached with `VM::set_hook`.
+///
+/// Every callback returns whether the VM should pause. `before_instruction`
+/// pauses with pc still on the instruction it was shown; the others let the
+/// current instruction finish first. Either way `run_partial` returns
+/// `Outcome::Paused` and picks up where it left off when called again.
+/// Output always hands control back with `Outcome::SentOutput`, so
+/// `on_output` can't pause any further than that. A pause asked for by
+/// another callback while an output instruction runs, like a watched read
+/// by its operand, comes as `Outcome::Paused` on the next call instead.
+///
+/// Reads and writes are only reported for addresses inside the ranges given
+/// to `VM::watch_reads` and `VM::watch_writes`. Reads are data reads made by
+/// operands, not the VM fetching instructions.
+pub trait Hook<W>: Send {
+    fn before_instruction(&mut self, _vm: &VM<W>) -> bool {
+        false
+    }
+
+    fn on_read(&mut self, _address: usize, _value: &W) -> bool {
+        false
+    }
+
+    fn on_write(&mut self, _address: usize, _old: &W, _new: &W) -> bool {
+        false
+    }
+
+    fn on_rb_change(&mut self, _old: i64, _new: i64) -> bool {
+        false
+    }
+
+    fn on_input(&mut self, _value: &W) -> bool {
+        false
+    }
+
+    fn on_output(&mut self, _value: &W) -> bool {
+        false
+    }
+}
+
+/// A hook that pauses the VM once it has executed `remaining` instructions,
+/// for running programs that might never halt.
+#[derive(Debug, Clone, Copy)]
+pub struct Fuel {
+    pub remaining: u64,
+}
+
+impl<W> Hook<W> for Fuel {
+    fn before_instruction(&mut self, _vm: &VM<W>) -> bool {
+        if self.remaining == 0 {
+            return true;
+        }
+
+        self.remaining -= 1;
+        false
+    }
+}
+
+pub(crate) type SharedHook<W> = Arc<Mutex<dyn Hook<W>>>;
+
+#[derive(Clone)]
+pub(crate) struct Hooks<W> {
+    pub hook: Option<SharedHook<W>>,
+    pub reads: Vec<Range<usize>>,
+    pub writes: Vec<Range<usize>>,
+
+    /// Set by a callback that wants to pause once the current instruction
+    /// has finished.
+    pub pause_requested: bool,
+
+    /// Set when `before_instruction` paused the VM, or an instruction it
+    /// was already shown had to wait for input, so that resuming doesn't
+    /// immediately pause on the same instruction again.
+    pub resuming: bool,
+}
+
+impl<W> Default for Hooks<W> {
+    fn default() -> Self {
+        Hooks {
+            hook: None,
+            reads: Vec::new(),
+            writes: Vec::new(),
+            pause_requested: false,
+            resuming: false,
+        }
+    }
+}
+
+impl<W> Hooks<W> {
+    pub fn watching_reads(&self, address: usize) -> Option<SharedHook<W>> {
+        if self.reads.iter().any(|range| range.contains(&address)) {
+            self.hook.clone()
+        } else {
+            None
+        }
+    }
+
+    pub fn watching_writes(&self, address: usize) -> Option<SharedHook<W>> {
+        if self.writes.iter().any(|range| range.contains(&address)) {
+            self.hook.clone()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,33 @@
+pub mod adventure;
+pub mod arcade;
+pub mod asm;
+pub mod beam;
+pub mod circuit;
+pub mod conformance;
+pub mod diff;
+pub mod disasm;
+pub mod grid;
+pub mod link;
+pub mod maze;
+pub mod network;
+pub mod paint;
+pub mod scaffold;
+pub mod springscript;
+
+mod cached;
+mod custom;
+mod hooks;
+mod machine;
+mod oracle;
+mod vm;
+mod word;
+
+pub use num_bigint::BigInt;
+
+pub use cached::CachedVM;
+pub use custom::CustomOp;
+pub use hooks::{Fuel, Hook};
+pub use machine::IntcodeMachine;
+pub use oracle::{QueryError, QueryOracle};
+pub use vm::{Arithmetic, CellKind, Error, Outcome, SelfModification, VM};
+pub use word::Word;
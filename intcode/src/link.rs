@@ -0,0 +1,100 @@
+use std::{collections::HashMap, fmt};
+
+use crate::asm::{assemble, Object};
+
+/// Placed before every linked program. Starts the stack right after the
+/// program and calls `main` following the calling convention in `asm`, so
+/// that `main` can return to halt.
+static PRELUDE: &str = "
+.import main, __stack
+    arb __stack
+    call main, 0
+    end
+";
+
+static STDLIB: &[(&str, &str)] = &[
+    ("print_number", include_str!("../stdlib/print_number.asm")),
+    ("read_line", include_str!("../stdlib/read_line.asm")),
+    ("mul_loop", include_str!("../stdlib/mul_loop.asm")),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// An object imports a label that no object exports. Missing `main`
+    /// shows up as this too.
+    UndefinedSymbol { name: String },
+
+    /// More than one object exports the same label.
+    DuplicateSymbol { name: String },
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkError::UndefinedSymbol { name } => write!(formatter, "{} is never exported", name),
+            LinkError::DuplicateSymbol { name } => {
+                write!(formatter, "{} is exported more than once", name)
+            }
+        }
+    }
+}
+
+/// The objects making up the standard library, for linking into programs
+/// that want them.
+pub fn stdlib() -> Vec<Object> {
+    STDLIB
+        .iter()
+        .map(|(name, source)| {
+            assemble(source).unwrap_or_else(|error| panic!("stdlib {}: {}", name, error))
+        })
+        .collect()
+}
+
+/// Lays the objects out one after another and patches every address, giving
+/// a program that can be loaded into a VM. Execution starts at `main`, with
+/// the stack growing upwards from the end of the program.
+pub fn link(objects: &[Object]) -> Result<Vec<i64>, LinkError> {
+    let prelude = assemble(PRELUDE).expect("prelude doesn't assemble");
+    let objects: Vec<&Object> = std::iter::once(&prelude).chain(objects).collect();
+
+    let mut bases = Vec::with_capacity(objects.len());
+    let mut len = 0;
+
+    for object in &objects {
+        bases.push(len);
+        len += object.code.len();
+    }
+
+    let mut symbols = HashMap::new();
+    symbols.insert("__stack".to_owned(), len);
+
+    for (object, &base) in objects.iter().zip(&bases) {
+        for (name, &offset) in &object.exports {
+            if symbols.insert(name.clone(), base + offset).is_some() {
+                return Err(LinkError::DuplicateSymbol { name: name.clone() });
+            }
+        }
+    }
+
+    let mut program = Vec::with_capacity(len);
+
+    for (object, &base) in objects.iter().zip(&bases) {
+        let mut code = object.code.clone();
+
+        for &cell in &object.relocations {
+            code[cell] += base as i64;
+        }
+
+        for (cell, name) in &object.imports {
+            let address = symbols
+                .get(name)
+                .ok_or_else(|| LinkError::UndefinedSymbol { name: name.clone() })?;
+
+            code[*cell] += *address as i64;
+        }
+
+        program.extend(code);
+    }
+
+    Ok(program)
+}
@@ -0,0 +1,49 @@
+use crate::{vm::Outcome, word::Word};
+
+/// The interface shared by every Intcode backend, so that puzzle solutions
+/// and test suites can be written once and run on whichever backend is
+/// fastest, or on all of them to compare.
+pub trait IntcodeMachine {
+    type Word: Word;
+
+    /// Replaces the program and resets pc, the relative base and the I/O
+    /// queues. Configuration like arithmetic policy is kept.
+    fn load(&mut self, program: &[Self::Word]);
+
+    fn push_input(&mut self, value: Self::Word);
+
+    /// Takes everything the program has output since the last drain.
+    fn drain_output(&mut self) -> Vec<Self::Word>;
+
+    /// Runs until the program outputs a value, needs input, stops, or
+    /// faults. Calling it again after `NeedsInput` or `SentOutput` resumes
+    /// the program.
+    fn run(&mut self) -> Outcome;
+
+    fn memory(&self) -> &[Self::Word];
+
+    /// Limits how many more instructions `run` executes before it returns
+    /// `Outcome::Paused`, or lifts the limit with `None`. Machines that
+    /// don't run instructions one at a time, like test doubles, ignore it.
+    fn set_fuel(&mut self, _fuel: Option<u64>) {}
+
+    /// Runs until the program stops, collecting everything it outputs.
+    /// Panics if it faults, runs out of input, or pauses before the end,
+    /// like when its fuel runs out. Use `run` to handle those.
+    fn run_to_end(&mut self) -> Vec<Self::Word> {
+        let mut output = Vec::new();
+
+        loop {
+            match self.run() {
+                Outcome::Terminated => break,
+                Outcome::SentOutput => output.extend(self.drain_output()),
+                Outcome::Paused => panic!("VM paused before the end"),
+                Outcome::NeedsInput => panic!("System starved for input"),
+                Outcome::Error(error) => panic!("VM faulted: {:?}", error),
+            }
+        }
+
+        output.extend(self.drain_output());
+        output
+    }
+}
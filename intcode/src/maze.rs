@@ -0,0 +1,200 @@
+//! The repair droid from day 15. The program reads a movement command, 1 to
+//! 4 for north, south, west and east, and replies with what happened: 0 if
+//! it hit a wall, 1 if it moved, or 2 if it moved and found the oxygen
+//! system.
+//!
+//! Instead of walking the droid back and forth, the explorer forks the
+//! machine at every open cell and tries each direction on a copy.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    grid,
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    pub fn command(self) -> i32 {
+        match self {
+            Direction::North => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+            Direction::East => 4,
+        }
+    }
+
+    /// The change in position from moving one cell, with north at the top
+    /// of the maze as it's drawn.
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::East => (1, 0),
+        }
+    }
+}
+
+fn step((x, y): (i64, i64), direction: Direction) -> (i64, i64) {
+    let (dx, dy) = direction.offset();
+    (x + dx, y + dy)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeError {
+    Fault(Error),
+
+    /// The program replied with something other than 0, 1 or 2.
+    InvalidStatus(Option<i64>),
+
+    /// The program asked for another command before replying to the last.
+    NoReply,
+
+    /// The program stopped while the droid was still exploring.
+    Halted,
+
+    /// The machine paused, like when its fuel ran out, before replying.
+    Paused,
+}
+
+/// Sends one movement command and waits for the reply.
+pub fn command<M: IntcodeMachine>(droid: &mut M, direction: Direction) -> Result<Cell, MazeError> {
+    droid.push_input(M::Word::from(direction.command()));
+
+    match droid.run() {
+        Outcome::SentOutput => {
+            let status = droid.drain_output().first().and_then(Word::to_i64);
+
+            match status {
+                Some(0) => Ok(Cell::Wall),
+                Some(1) => Ok(Cell::Open),
+                Some(2) => Ok(Cell::Oxygen),
+                other => Err(MazeError::InvalidStatus(other)),
+            }
+        }
+        Outcome::NeedsInput => Err(MazeError::NoReply),
+        Outcome::Terminated => Err(MazeError::Halted),
+        Outcome::Paused => Err(MazeError::Paused),
+        Outcome::Error(error) => Err(MazeError::Fault(error)),
+    }
+}
+
+/// Every cell the droid has seen, relative to where it started at (0, 0).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Maze {
+    pub cells: HashMap<(i64, i64), Cell>,
+}
+
+impl Maze {
+    /// Where the oxygen system is, if the droid found it.
+    pub fn oxygen(&self) -> Option<(i64, i64)> {
+        self.cells
+            .iter()
+            .find(|(_, &cell)| cell == Cell::Oxygen)
+            .map(|(&position, _)| position)
+    }
+
+    /// How many steps it takes to reach each open cell from `from`, without
+    /// going through walls or cells that haven't been seen.
+    pub fn distances(&self, from: (i64, i64)) -> HashMap<(i64, i64), usize> {
+        let mut distances = HashMap::new();
+        let mut to_visit = VecDeque::new();
+        to_visit.push_back((from, 0));
+
+        while let Some((position, distance)) = to_visit.pop_front() {
+            match self.cells.get(&position) {
+                Some(Cell::Open) | Some(Cell::Oxygen) => {}
+                _ => continue,
+            }
+
+            if distances.contains_key(&position) {
+                continue;
+            }
+
+            distances.insert(position, distance);
+
+            for &direction in &Direction::ALL {
+                to_visit.push_back((step(position, direction), distance + 1));
+            }
+        }
+
+        distances
+    }
+
+    pub fn shortest_path(&self, from: (i64, i64), to: (i64, i64)) -> Option<usize> {
+        self.distances(from).get(&to).copied()
+    }
+
+    /// How long it takes oxygen spreading from `from` to fill every open
+    /// cell it can reach, which is the distance to the furthest one.
+    pub fn fill_time(&self, from: (i64, i64)) -> usize {
+        self.distances(from).values().copied().max().unwrap_or(0)
+    }
+
+    /// Draws the maze as text, with `D` for the start and `O` for the
+    /// oxygen system. Cells that were never seen are blank.
+    pub fn render(&self) -> String {
+        grid::render(&self.cells, |position, cell| match cell {
+            _ if position == (0, 0) => 'D',
+            Some(Cell::Wall) => '#',
+            Some(Cell::Open) => '.',
+            Some(Cell::Oxygen) => 'O',
+            None => ' ',
+        })
+    }
+}
+
+/// Maps every cell reachable from where the droid starts. The droid is
+/// forked once for every direction tried from every open cell, so the
+/// original is left where it was.
+pub fn explore<M: IntcodeMachine + Clone>(droid: &M) -> Result<Maze, MazeError> {
+    let mut maze = Maze::default();
+    maze.cells.insert((0, 0), Cell::Open);
+
+    let mut to_visit = VecDeque::new();
+    to_visit.push_back(((0, 0), droid.clone()));
+
+    while let Some((position, droid)) = to_visit.pop_front() {
+        for &direction in &Direction::ALL {
+            let next = step(position, direction);
+
+            if maze.cells.contains_key(&next) {
+                continue;
+            }
+
+            let mut fork = droid.clone();
+            let cell = command(&mut fork, direction)?;
+            maze.cells.insert(next, cell);
+
+            if cell != Cell::Wall {
+                to_visit.push_back((next, fork));
+            }
+        }
+    }
+
+    Ok(maze)
+}
@@ -0,0 +1,228 @@
+//! The packet-switched network from day 23. Every computer reads its address
+//! when it boots, then sends packets by outputting `(destination, x, y)` and
+//! receives them by reading `x` and `y`. Reading from an empty queue gives
+//! -1 instead of blocking.
+//!
+//! The network runs in rounds, one turn per computer in address order, so
+//! it behaves the same every time. On its turn a computer reads everything
+//! queued for it, or a single -1 if nothing is, and runs until it asks for
+//! input again. A round where no packets were received or sent, and no
+//! computer is partway through sending one, means the network is idle, and
+//! the NAT at address 255 wakes it back up by sending the last packet it got
+//! to address 0.
+
+use std::collections::VecDeque;
+
+use crate::{
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+pub const NAT: usize = 255;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet<W> {
+    pub x: W,
+    pub y: W,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<W> {
+    /// A computer sent a packet, possibly to the NAT.
+    Sent {
+        from: usize,
+        to: usize,
+        packet: Packet<W>,
+    },
+
+    /// The network went idle and the NAT sent this packet to address 0.
+    Woke(Packet<W>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkError<W> {
+    Fault {
+        address: usize,
+        error: Error,
+    },
+
+    /// A computer stopped. Day 23's computers are meant to run forever.
+    Halted {
+        address: usize,
+    },
+
+    /// A computer paused, like when its fuel ran out.
+    Paused {
+        address: usize,
+    },
+
+    /// A computer sent a packet to an address that isn't on the network.
+    UnknownAddress {
+        from: usize,
+        to: W,
+    },
+
+    /// The network went idle before the NAT had anything to send.
+    Deadlock,
+}
+
+struct Node<M: IntcodeMachine> {
+    machine: M,
+    queue: VecDeque<Packet<M::Word>>,
+
+    /// Outputs that haven't made up a whole packet yet.
+    pending: Vec<M::Word>,
+}
+
+pub struct Network<M: IntcodeMachine> {
+    nodes: Vec<Node<M>>,
+
+    /// The last packet sent to the NAT, if any.
+    pub nat: Option<Packet<M::Word>>,
+
+    /// How many rounds have run so far.
+    pub rounds: usize,
+}
+
+impl<M: IntcodeMachine + Default> Network<M> {
+    /// Boots `size` computers running `program`. Each one reads its address
+    /// on its first turn, before anything else queued for it.
+    pub fn new(program: &[M::Word], size: usize) -> Self {
+        let mut nodes = Vec::with_capacity(size);
+
+        for address in 0..size {
+            let mut machine = M::default();
+            machine.load(program);
+            machine.push_input(M::Word::from(address as i32));
+
+            nodes.push(Node {
+                machine,
+                queue: VecDeque::new(),
+                pending: Vec::new(),
+            });
+        }
+
+        Network {
+            nodes,
+            nat: None,
+            rounds: 0,
+        }
+    }
+}
+
+impl<M: IntcodeMachine> Network<M> {
+    /// Runs one computer until it asks for input with nothing left to read,
+    /// returning the packets it sent.
+    fn turn(&mut self, address: usize) -> Result<Vec<Event<M::Word>>, NetworkError<M::Word>> {
+        let size = self.nodes.len();
+        let node = &mut self.nodes[address];
+        let mut sent = Vec::new();
+
+        loop {
+            match node.machine.run() {
+                Outcome::NeedsInput => return Ok(sent),
+                Outcome::SentOutput => {
+                    node.pending.extend(node.machine.drain_output());
+
+                    while node.pending.len() >= 3 {
+                        let mut parts = node.pending.drain(..3);
+                        let to = parts.next().unwrap();
+                        let x = parts.next().unwrap();
+                        let y = parts.next().unwrap();
+                        drop(parts);
+
+                        let to = match to.to_address() {
+                            Some(to) if to < size || to == NAT => to,
+                            _ => return Err(NetworkError::UnknownAddress { from: address, to }),
+                        };
+
+                        sent.push(Event::Sent {
+                            from: address,
+                            to,
+                            packet: Packet { x, y },
+                        });
+                    }
+                }
+                Outcome::Paused => return Err(NetworkError::Paused { address }),
+                Outcome::Terminated => return Err(NetworkError::Halted { address }),
+                Outcome::Error(error) => return Err(NetworkError::Fault { address, error }),
+            }
+        }
+    }
+
+    fn deliver(&mut self, to: usize, packet: Packet<M::Word>) {
+        if to == NAT {
+            self.nat = Some(packet);
+        } else {
+            self.nodes[to].queue.push_back(packet);
+        }
+    }
+
+    /// Runs every computer for one turn, then wakes the network through the
+    /// NAT if nothing happened. Returns everything that happened, in order.
+    pub fn round(&mut self) -> Result<Vec<Event<M::Word>>, NetworkError<M::Word>> {
+        let mut events = Vec::new();
+        let mut busy = false;
+
+        for address in 0..self.nodes.len() {
+            let node = &mut self.nodes[address];
+
+            if node.queue.is_empty() {
+                node.machine.push_input(M::Word::from(-1));
+            } else {
+                busy = true;
+
+                for packet in node.queue.drain(..) {
+                    node.machine.push_input(packet.x);
+                    node.machine.push_input(packet.y);
+                }
+            }
+
+            let sent = self.turn(address)?;
+            busy |= !sent.is_empty();
+
+            // packets land straight away, so computers later in the round
+            // can read them on this same turn
+            for event in sent {
+                if let Event::Sent { to, packet, .. } = &event {
+                    self.deliver(*to, packet.clone());
+                }
+
+                events.push(event);
+            }
+        }
+
+        self.rounds += 1;
+
+        // a computer that has only sent part of a packet isn't done yet
+        busy |= self.nodes.iter().any(|node| !node.pending.is_empty());
+
+        if !busy {
+            let packet = self.nat.clone().ok_or(NetworkError::Deadlock)?;
+
+            match self.nodes.first_mut() {
+                Some(node) => node.queue.push_back(packet.clone()),
+                None => return Err(NetworkError::Deadlock),
+            }
+
+            events.push(Event::Woke(packet));
+        }
+
+        Ok(events)
+    }
+
+    /// Runs rounds until `check` returns something for one of the events.
+    pub fn run_until<T>(
+        &mut self,
+        mut check: impl FnMut(&Event<M::Word>) -> Option<T>,
+    ) -> Result<T, NetworkError<M::Word>> {
+        loop {
+            for event in self.round()? {
+                if let Some(found) = check(&event) {
+                    return Ok(found);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    Fault(Error),
+
+    /// The program wanted more input than the query gave it.
+    Starved,
+
+    /// The machine paused, like when its fuel ran out, before the program
+    /// halted.
+    Paused,
+
+    /// A value to query with doesn't fit in the machine's words.
+    InputTooLarge(i64),
+}
+
+/// Answers queries against a program that's a pure function of its input,
+/// like the drone system from day 19 or the amplifiers from day 7.
+///
+/// Every query runs on a fresh copy of the machine it was made with, and
+/// each answer is remembered so asking again costs nothing.
+pub struct QueryOracle<M: IntcodeMachine> {
+    pristine: M,
+    answers: HashMap<Vec<M::Word>, Vec<M::Word>>,
+
+    /// How many queries actually ran the program.
+    pub runs: usize,
+
+    /// How many queries were answered from memory.
+    pub hits: usize,
+}
+
+impl<M> QueryOracle<M>
+where
+    M: IntcodeMachine + Clone,
+    M::Word: Hash + Eq,
+{
+    pub fn new(pristine: M) -> Self {
+        QueryOracle {
+            pristine,
+            answers: HashMap::new(),
+            runs: 0,
+            hits: 0,
+        }
+    }
+
+    pub fn from_tape(program: &[M::Word]) -> Self
+    where
+        M: Default,
+    {
+        let mut pristine = M::default();
+        pristine.load(program);
+        QueryOracle::new(pristine)
+    }
+
+    /// Runs the program with `inputs` and returns everything it output.
+    pub fn query(&mut self, inputs: &[M::Word]) -> Result<Vec<M::Word>, QueryError> {
+        if let Some(answer) = self.answers.get(inputs) {
+            self.hits += 1;
+            return Ok(answer.clone());
+        }
+
+        let mut machine = self.pristine.clone();
+        for input in inputs {
+            machine.push_input(input.clone());
+        }
+
+        let mut outputs = Vec::new();
+
+        loop {
+            match machine.run() {
+                Outcome::Terminated => break,
+                Outcome::SentOutput => outputs.extend(machine.drain_output()),
+                Outcome::Paused => return Err(QueryError::Paused),
+                Outcome::NeedsInput => return Err(QueryError::Starved),
+                Outcome::Error(error) => return Err(QueryError::Fault(error)),
+            }
+        }
+
+        outputs.extend(machine.drain_output());
+
+        self.runs += 1;
+        self.answers.insert(inputs.to_vec(), outputs.clone());
+        Ok(outputs)
+    }
+
+    /// Like `query`, for programs that answer with a single value. Programs
+    /// that say nothing answer 0.
+    pub fn ask(&mut self, inputs: &[M::Word]) -> Result<M::Word, QueryError> {
+        let outputs = self.query(inputs)?;
+        Ok(outputs.last().cloned().unwrap_or_else(|| M::Word::from(0)))
+    }
+}
@@ -0,0 +1,220 @@
+//! The hull-painting robot from day 11: a program sees the colour of the
+//! panel under the robot, then answers with a colour to paint it and which
+//! way to turn before moving forward one panel.
+
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    grid,
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    White,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    pub fn turn_left(self) -> Self {
+        match self {
+            Heading::Up => Heading::Left,
+            Heading::Left => Heading::Down,
+            Heading::Down => Heading::Right,
+            Heading::Right => Heading::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.turn_left().turn_left().turn_left()
+    }
+
+    /// The change in position from moving one panel forward, so `Up` is
+    /// towards the top of the hull as it's drawn.
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Heading::Up => (0, -1),
+            Heading::Right => (1, 0),
+            Heading::Down => (0, 1),
+            Heading::Left => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaintError {
+    Fault(Error),
+
+    /// The program asked for a colour that isn't 0 or 1.
+    InvalidColor(Option<i64>),
+
+    /// The program asked for a turn that isn't 0 or 1.
+    InvalidTurn(Option<i64>),
+
+    /// The machine paused, like when its fuel ran out, before the program halted.
+    Paused,
+}
+
+/// Every panel that has been painted, and what colour it ended up. Panels
+/// that were never painted are black.
+#[derive(Debug, Clone, Default)]
+pub struct Hull {
+    pub panels: HashMap<(i64, i64), Color>,
+}
+
+impl Hull {
+    pub fn color(&self, position: (i64, i64)) -> Color {
+        self.panels.get(&position).copied().unwrap_or(Color::Black)
+    }
+
+    pub fn paint(&mut self, position: (i64, i64), color: Color) {
+        self.panels.insert(position, color);
+    }
+
+    /// How many panels were painted at least once, whatever colour.
+    pub fn painted(&self) -> usize {
+        self.panels.len()
+    }
+
+    /// The smallest rectangle holding every white panel, as its top left and
+    /// bottom right corners.
+    pub fn bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        let white = self
+            .panels
+            .iter()
+            .filter(|(_, &color)| color == Color::White)
+            .map(|(&position, _)| position);
+
+        grid::bounds(white)
+    }
+
+    fn rows(&self) -> Vec<Vec<Color>> {
+        let ((left, top), (right, bottom)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return Vec::new(),
+        };
+
+        (top..=bottom)
+            .map(|y| (left..=right).map(|x| self.color((x, y))).collect())
+            .collect()
+    }
+
+    /// Draws the white panels the same way day 8 draws its image.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        for row in self.rows() {
+            for color in row {
+                let char = match color {
+                    Color::Black => "  ",
+                    Color::White => "##",
+                };
+
+                write!(output, "{}", char).unwrap();
+            }
+
+            writeln!(output).unwrap();
+        }
+
+        output
+    }
+
+    /// Draws the white panels as a plain PBM image, which most image viewers
+    /// can open.
+    pub fn to_pbm(&self) -> String {
+        let rows = self.rows();
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+
+        let mut output = format!("P1\n{} {}\n", width, rows.len());
+
+        for row in rows {
+            // in PBM, 1 is black
+            let pixels: Vec<&str> = row
+                .iter()
+                .map(|color| match color {
+                    Color::Black => "1",
+                    Color::White => "0",
+                })
+                .collect();
+
+            writeln!(output, "{}", pixels.join(" ")).unwrap();
+        }
+
+        output
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+    pub position: (i64, i64),
+    pub heading: Heading,
+}
+
+impl Default for Robot {
+    fn default() -> Self {
+        Robot {
+            position: (0, 0),
+            heading: Heading::Up,
+        }
+    }
+}
+
+impl Robot {
+    /// Runs the robot's program until it halts, painting the hull as it goes.
+    pub fn run<M: IntcodeMachine>(
+        &mut self,
+        brain: &mut M,
+        hull: &mut Hull,
+    ) -> Result<(), PaintError> {
+        let mut outputs = Vec::new();
+
+        loop {
+            match brain.run() {
+                Outcome::Terminated => return Ok(()),
+                Outcome::NeedsInput => {
+                    let color = match hull.color(self.position) {
+                        Color::Black => 0,
+                        Color::White => 1,
+                    };
+
+                    brain.push_input(M::Word::from(color));
+                }
+                Outcome::SentOutput => {
+                    outputs.extend(brain.drain_output().iter().map(Word::to_i64));
+
+                    if outputs.len() >= 2 {
+                        let color = match outputs[0] {
+                            Some(0) => Color::Black,
+                            Some(1) => Color::White,
+                            other => return Err(PaintError::InvalidColor(other)),
+                        };
+
+                        self.heading = match outputs[1] {
+                            Some(0) => self.heading.turn_left(),
+                            Some(1) => self.heading.turn_right(),
+                            other => return Err(PaintError::InvalidTurn(other)),
+                        };
+
+                        hull.paint(self.position, color);
+
+                        let (dx, dy) = self.heading.offset();
+                        self.position = (self.position.0 + dx, self.position.1 + dy);
+                        outputs.drain(..2);
+                    }
+                }
+                Outcome::Paused => return Err(PaintError::Paused),
+                Outcome::Error(error) => return Err(PaintError::Fault(error)),
+            }
+        }
+    }
+}
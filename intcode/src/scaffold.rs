@@ -0,0 +1,394 @@
+//! The scaffolding camera and vacuum robot from day 17. The program draws
+//! the scaffolding as ASCII, `#` for scaffold and `^`, `v`, `<` or `>` for
+//! the robot, then in part two asks for a movement routine: a main routine
+//! calling functions `A`, `B` and `C`, each line at most 20 characters.
+//!
+//! `navigate` does the whole trip: it reads the picture, works out a path
+//! that follows the scaffolding to its end, squeezes the path into a
+//! routine, and feeds it back to the robot.
+
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    machine::IntcodeMachine,
+    paint::Heading,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+pub const MAX_FUNCTIONS: usize = 3;
+pub const MAX_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaffoldError {
+    Fault(Error),
+
+    /// The picture doesn't have the robot in it, or it's tumbling through
+    /// space as an `X`.
+    NoRobot,
+
+    /// The scaffolding loops back on itself, so following it never ends.
+    Loop,
+
+    /// The path doesn't fit into a routine.
+    Incompressible,
+
+    /// The program asked for more input after the routine.
+    Starved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Left,
+    Right,
+    Forward(usize),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Left => write!(formatter, "L"),
+            Token::Right => write!(formatter, "R"),
+            Token::Forward(distance) => write!(formatter, "{}", distance),
+        }
+    }
+}
+
+fn join<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+    items
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Writes a path the way the robot reads it, like `R,8,L,10`.
+pub fn format_path(path: &[Token]) -> String {
+    join(path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pose {
+    pub position: (i64, i64),
+    pub heading: Heading,
+}
+
+/// One picture from the camera.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scaffold {
+    pub rows: Vec<Vec<u8>>,
+}
+
+impl Scaffold {
+    /// Reads the picture from the start of the camera's output, stopping at
+    /// the first blank line after it. Anything else, like prompts, is
+    /// skipped.
+    pub fn parse(text: &str) -> Scaffold {
+        let rows = text
+            .lines()
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .filter(|line| line.bytes().all(|c| b".#^v<>X".contains(&c)))
+            .map(|line| line.as_bytes().to_vec())
+            .collect();
+
+        Scaffold { rows }
+    }
+
+    pub fn get(&self, (x, y): (i64, i64)) -> Option<u8> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        self.rows.get(y as usize)?.get(x as usize).copied()
+    }
+
+    /// Whether there's scaffolding at a position. The robot is always on
+    /// some.
+    pub fn is_scaffold(&self, position: (i64, i64)) -> bool {
+        match self.get(position) {
+            Some(b'.') | Some(b'X') | None => false,
+            Some(_) => true,
+        }
+    }
+
+    /// Every scaffold with scaffolds on all four sides.
+    pub fn intersections(&self) -> Vec<(i64, i64)> {
+        let mut intersections = Vec::new();
+
+        for (y, row) in self.rows.iter().enumerate() {
+            for x in 0..row.len() {
+                let (x, y) = (x as i64, y as i64);
+                let around = [(x, y), (x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+
+                if around.iter().all(|&position| self.is_scaffold(position)) {
+                    intersections.push((x, y));
+                }
+            }
+        }
+
+        intersections
+    }
+
+    /// The sum of `x * y` over every intersection, for part one.
+    pub fn alignment(&self) -> i64 {
+        self.intersections().iter().map(|(x, y)| x * y).sum()
+    }
+
+    pub fn robot(&self) -> Option<Pose> {
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                let heading = match c {
+                    b'^' => Heading::Up,
+                    b'v' => Heading::Down,
+                    b'<' => Heading::Left,
+                    b'>' => Heading::Right,
+                    _ => continue,
+                };
+
+                return Some(Pose {
+                    position: (x as i64, y as i64),
+                    heading,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Follows the scaffolding from the robot until it ends, going straight
+    /// across intersections and turning wherever it bends. Coming back to a
+    /// corner facing the same way as before means it never ends.
+    pub fn path(&self) -> Result<Vec<Token>, ScaffoldError> {
+        let Pose {
+            mut position,
+            mut heading,
+        } = self.robot().ok_or(ScaffoldError::NoRobot)?;
+
+        let ahead = |position: (i64, i64), heading: Heading| {
+            let (dx, dy) = heading.offset();
+            (position.0 + dx, position.1 + dy)
+        };
+
+        let mut path = Vec::new();
+        let mut corners = HashSet::new();
+
+        // the robot might start off facing away from the scaffolding
+        if !self.is_scaffold(ahead(position, heading))
+            && !self.is_scaffold(ahead(position, heading.turn_left()))
+            && !self.is_scaffold(ahead(position, heading.turn_right()))
+            && self.is_scaffold(ahead(position, heading.turn_left().turn_left()))
+        {
+            path.extend(&[Token::Right, Token::Right]);
+            heading = heading.turn_left().turn_left();
+        }
+
+        loop {
+            let mut distance = 0;
+            while self.is_scaffold(ahead(position, heading)) {
+                position = ahead(position, heading);
+                distance += 1;
+            }
+
+            if distance > 0 {
+                path.push(Token::Forward(distance));
+            }
+
+            if self.is_scaffold(ahead(position, heading.turn_left())) {
+                path.push(Token::Left);
+                heading = heading.turn_left();
+            } else if self.is_scaffold(ahead(position, heading.turn_right())) {
+                path.push(Token::Right);
+                heading = heading.turn_right();
+            } else {
+                return Ok(path);
+            }
+
+            if !corners.insert((position, heading)) {
+                return Err(ScaffoldError::Loop);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Scaffold {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(formatter, "{}", String::from_utf8_lossy(row))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A main routine and the functions it calls. Main is a list of indices
+/// into `functions`, where 0 is `A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Routine {
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<Token>>,
+}
+
+impl Routine {
+    /// The lines the robot asks for: main, then `A`, `B` and `C`. Functions
+    /// that aren't needed are left empty.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![join(
+            self.main.iter().map(|&index| (b'A' + index as u8) as char),
+        )];
+
+        for index in 0..MAX_FUNCTIONS {
+            lines.push(
+                self.functions
+                    .get(index)
+                    .map(|function| format_path(function))
+                    .unwrap_or_default(),
+            );
+        }
+
+        lines
+    }
+
+    /// The path the routine walks.
+    pub fn expand(&self) -> Vec<Token> {
+        self.main
+            .iter()
+            .flat_map(|&index| self.functions[index].iter().copied())
+            .collect()
+    }
+}
+
+fn fits(path: &[Token], limit: usize) -> bool {
+    format_path(path).len() <= limit
+}
+
+fn compress_from<'a>(
+    path: &'a [Token],
+    limit: usize,
+    main: &mut Vec<usize>,
+    functions: &mut Vec<&'a [Token]>,
+) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+
+    // every call in main takes a letter and a comma
+    if main.len() * 2 + 1 > limit {
+        return false;
+    }
+
+    for index in 0..functions.len() {
+        if path.starts_with(functions[index]) {
+            main.push(index);
+
+            if compress_from(&path[functions[index].len()..], limit, main, functions) {
+                return true;
+            }
+
+            main.pop();
+        }
+    }
+
+    if functions.len() < MAX_FUNCTIONS {
+        // longer functions first, since they make for a shorter main
+        let longest = (1..=path.len())
+            .take_while(|&len| fits(&path[..len], limit))
+            .last()
+            .unwrap_or(0);
+
+        for len in (1..=longest).rev() {
+            functions.push(&path[..len]);
+            main.push(functions.len() - 1);
+
+            if compress_from(&path[len..], limit, main, functions) {
+                return true;
+            }
+
+            main.pop();
+            functions.pop();
+        }
+    }
+
+    false
+}
+
+/// Splits a path into a main routine and up to three functions, with every
+/// line at most `limit` characters long.
+pub fn compress(path: &[Token], limit: usize) -> Option<Routine> {
+    let mut main = Vec::new();
+    let mut functions = Vec::new();
+
+    if compress_from(path, limit, &mut main, &mut functions) {
+        Some(Routine {
+            main,
+            functions: functions.into_iter().map(<[Token]>::to_vec).collect(),
+        })
+    } else {
+        None
+    }
+}
+
+fn read_text<M: IntcodeMachine>(
+    machine: &mut M,
+    text: &mut String,
+    last: &mut Option<i64>,
+) -> Result<bool, ScaffoldError> {
+    loop {
+        match machine.run() {
+            Outcome::Terminated => return Ok(false),
+            Outcome::NeedsInput => return Ok(true),
+            Outcome::SentOutput | Outcome::Paused => {
+                for value in machine.drain_output() {
+                    match value.to_i64() {
+                        Some(code @ 0..=127) => text.push(code as u8 as char),
+                        other => *last = other,
+                    }
+                }
+            }
+            Outcome::Error(error) => return Err(ScaffoldError::Fault(error)),
+        }
+    }
+}
+
+/// Runs the camera until it stops or asks for input, and reads the
+/// picture it drew.
+pub fn capture<M: IntcodeMachine>(machine: &mut M) -> Result<Scaffold, ScaffoldError> {
+    let mut text = String::new();
+    read_text(machine, &mut text, &mut None)?;
+    Ok(Scaffold::parse(&text))
+}
+
+/// Sends a routine to the robot, with the continuous video feed off, and
+/// runs it to the end. Returns the last thing it output that wasn't ASCII,
+/// which is the dust collected.
+pub fn feed<M: IntcodeMachine>(
+    machine: &mut M,
+    routine: &Routine,
+) -> Result<Option<i64>, ScaffoldError> {
+    let mut lines = routine.lines();
+    lines.push("n".to_owned());
+
+    for line in lines {
+        for byte in line.bytes().chain(Some(b'\n')) {
+            machine.push_input(M::Word::from(byte as i32));
+        }
+    }
+
+    let mut last = None;
+    if read_text(machine, &mut String::new(), &mut last)? {
+        return Err(ScaffoldError::Starved);
+    }
+
+    Ok(last)
+}
+
+/// The whole of part two: read the picture, find and compress the path,
+/// and walk it. The program needs 2 written to address 0 first, which is up
+/// to the caller.
+pub fn navigate<M: IntcodeMachine>(machine: &mut M) -> Result<Option<i64>, ScaffoldError> {
+    let scaffold = capture(machine)?;
+    let path = scaffold.path()?;
+    let routine = compress(&path, MAX_LENGTH).ok_or(ScaffoldError::Incompressible)?;
+
+    feed(machine, &routine)
+}
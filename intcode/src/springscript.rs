@@ -0,0 +1,703 @@
+//! A front end for the springdroid from day 21. The droid is programmed in
+//! springscript, which has two writable registers, `T` and `J`, read-only
+//! sensors `A` through `D` (or `I` when running), and only `AND`, `OR` and
+//! `NOT`. The droid jumps whenever `J` ends up true, and a script can be at
+//! most 15 instructions long.
+//!
+//! Instead of writing springscript by hand, write a boolean expression over
+//! the sensors like `!(A & B & C) & D`. It gets flattened into an OR of
+//! ANDs, and each AND is built in `T` and ORed into `J`.
+//!
+//! `search` goes one further and finds a script by itself. It simulates the
+//! droid over every hull it has seen fall through, picks jump decisions that
+//! get across all of them, and tries the result on the real program.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    machine::IntcodeMachine,
+    vm::{Error, Outcome},
+    word::Word,
+};
+
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    /// How many sensors the droid has in this mode.
+    pub fn sensors(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(formatter, "WALK"),
+            Mode::Run => write!(formatter, "RUN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    Syntax {
+        position: usize,
+        message: String,
+    },
+
+    /// The expression reads a sensor the droid doesn't have in this mode.
+    SensorOutOfRange {
+        sensor: char,
+        mode: Mode,
+    },
+
+    TooLong {
+        count: usize,
+    },
+
+    Fault(Error),
+
+    /// The program asked for more input after reading the script.
+    Starved,
+
+    /// No jump decisions get across every hull seen so far, at least not in
+    /// a short enough script.
+    Unsolvable,
+
+    /// The droid fell but its output didn't show the hull.
+    UnreadableFrame,
+
+    GaveUp {
+        attempts: usize,
+    },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::Syntax { position, message } => {
+                write!(formatter, "at {}: {}", position, message)
+            }
+            ScriptError::SensorOutOfRange { sensor, mode } => {
+                write!(
+                    formatter,
+                    "sensor {} isn't available in {} mode",
+                    sensor, mode
+                )
+            }
+            ScriptError::TooLong { count } => write!(
+                formatter,
+                "script is {} instructions long, the limit is {}",
+                count, MAX_INSTRUCTIONS
+            ),
+            ScriptError::Fault(error) => write!(formatter, "VM faulted: {:?}", error),
+            ScriptError::Starved => write!(formatter, "program wanted more than the script"),
+            ScriptError::Unsolvable => write!(formatter, "no script gets across every hull"),
+            ScriptError::UnreadableFrame => write!(formatter, "couldn't find the hull"),
+            ScriptError::GaveUp { attempts } => {
+                write!(formatter, "no luck after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    /// Sensor 0 is `A`, one tile ahead of the droid.
+    Sensor(u8),
+    T,
+    J,
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::Sensor(index) => write!(formatter, "{}", (b'A' + index) as char),
+            Register::T => write!(formatter, "T"),
+            Register::J => write!(formatter, "J"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Op,
+    pub source: Register,
+    pub target: Register,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+
+        write!(formatter, "{} {} {}", op, self.source, self.target)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Sensor(u8),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// A conjunction of sensors, as bitmasks of the ones that have to be ground
+/// and the ones that have to be holes. No bits at all means always true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Term {
+    pub ground: u16,
+    pub holes: u16,
+}
+
+impl Term {
+    fn and(self, other: Term) -> Option<Term> {
+        let term = Term {
+            ground: self.ground | other.ground,
+            holes: self.holes | other.holes,
+        };
+
+        // A & !A is never true
+        if term.ground & term.holes == 0 {
+            Some(term)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this term is true everywhere `other` is.
+    fn covers(self, other: Term) -> bool {
+        self.ground & !other.ground == 0 && self.holes & !other.holes == 0
+    }
+
+    fn matches(self, state: u16) -> bool {
+        state & self.ground == self.ground && state & self.holes == 0
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.text.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+
+        self.text.get(self.position).copied()
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ScriptError> {
+        Err(ScriptError::Syntax {
+            position: self.position,
+            message: message.to_owned(),
+        })
+    }
+
+    fn or(&mut self) -> Result<Expr, ScriptError> {
+        let mut expr = self.and()?;
+
+        while self.peek() == Some(b'|') {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ScriptError> {
+        let mut expr = self.unary()?;
+
+        while self.peek() == Some(b'&') {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ScriptError> {
+        match self.peek() {
+            Some(b'!') => {
+                self.position += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let expr = self.or()?;
+
+                if self.peek() != Some(b')') {
+                    return self.error("expected ')'");
+                }
+
+                self.position += 1;
+                Ok(expr)
+            }
+            Some(sensor @ b'A'..=b'I') => {
+                self.position += 1;
+                Ok(Expr::Sensor(sensor - b'A'))
+            }
+            Some(_) => self.error("expected a sensor, '!' or '('"),
+            None => self.error("unexpected end of expression"),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(text: &str) -> Result<Expr, ScriptError> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+
+        let expr = parser.or()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(_) => parser.error("expected '&', '|' or the end"),
+        }
+    }
+
+    /// The expression as an OR of ANDs, with redundant terms dropped.
+    pub fn terms(&self) -> Vec<Term> {
+        simplify(self.dnf(false))
+    }
+
+    fn dnf(&self, negated: bool) -> Vec<Term> {
+        match (self, negated) {
+            (Expr::Sensor(index), false) => vec![Term {
+                ground: 1 << index,
+                holes: 0,
+            }],
+            (Expr::Sensor(index), true) => vec![Term {
+                ground: 0,
+                holes: 1 << index,
+            }],
+            (Expr::Not(inner), _) => inner.dnf(!negated),
+
+            // !(a & b) is !a | !b, and !(a | b) is !a & !b
+            (Expr::And(left, right), false) | (Expr::Or(left, right), true) => {
+                let left = left.dnf(negated);
+                let right = right.dnf(negated);
+
+                left.iter()
+                    .flat_map(|&a| right.iter().filter_map(move |&b| a.and(b)))
+                    .collect()
+            }
+            (Expr::Or(left, right), false) | (Expr::And(left, right), true) => {
+                let mut terms = left.dnf(negated);
+                terms.extend(right.dnf(negated));
+                terms
+            }
+        }
+    }
+}
+
+/// Drops duplicate terms and terms that another term already covers.
+fn simplify(terms: Vec<Term>) -> Vec<Term> {
+    let mut kept: Vec<Term> = Vec::new();
+
+    for term in terms {
+        if kept.iter().any(|other| other.covers(term)) {
+            continue;
+        }
+
+        kept.retain(|other| !term.covers(*other));
+        kept.push(term);
+    }
+
+    kept
+}
+
+fn sensors(mask: u16) -> impl Iterator<Item = Register> {
+    (0..16)
+        .filter(move |index| mask & (1 << index) != 0)
+        .map(|index| Register::Sensor(index as u8))
+}
+
+fn emit(instructions: &mut Vec<Instruction>, op: Op, source: Register, target: Register) {
+    instructions.push(Instruction { op, source, target });
+}
+
+/// Builds one term into `target`. If `clean` is set, `target` is known to
+/// be false already, which saves an instruction.
+fn build_term(instructions: &mut Vec<Instruction>, term: Term, target: Register, clean: bool) {
+    let mut ground = sensors(term.ground);
+    let mut holes = sensors(term.holes).collect::<Vec<_>>();
+
+    // !a & !b & ... is !(a | b | ...), so the holes go first as one OR
+    match holes.len() {
+        0 => match ground.next() {
+            Some(first) if clean => emit(instructions, Op::Or, first, target),
+            Some(first) => {
+                emit(instructions, Op::Not, first, target);
+                emit(instructions, Op::Not, target, target);
+            }
+
+            // the empty term, which is always true
+            None if clean => emit(instructions, Op::Not, target, target),
+            None => {
+                emit(instructions, Op::Not, Register::Sensor(0), target);
+                emit(instructions, Op::Or, Register::Sensor(0), target);
+            }
+        },
+        1 => emit(instructions, Op::Not, holes[0], target),
+        _ => {
+            let first = holes.remove(0);
+
+            if clean {
+                emit(instructions, Op::Or, first, target);
+            } else {
+                emit(instructions, Op::Not, first, target);
+                emit(instructions, Op::Not, target, target);
+            }
+
+            for hole in holes {
+                emit(instructions, Op::Or, hole, target);
+            }
+
+            emit(instructions, Op::Not, target, target);
+        }
+    }
+
+    for sensor in ground {
+        emit(instructions, Op::And, sensor, target);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+impl Script {
+    /// Compiles an OR of ANDs. Both registers start out false, so the first
+    /// term goes straight into `J` and the rest go through `T`.
+    pub fn from_terms(terms: &[Term], mode: Mode) -> Result<Script, ScriptError> {
+        let mut instructions = Vec::new();
+        let mut clean = true;
+
+        for (index, &term) in terms.iter().enumerate() {
+            let literals = (term.ground | term.holes).count_ones();
+
+            if index == 0 {
+                build_term(&mut instructions, term, Register::J, true);
+            } else if literals == 1 && term.ground != 0 {
+                let sensor = sensors(term.ground).next().unwrap();
+                emit(&mut instructions, Op::Or, sensor, Register::J);
+            } else {
+                build_term(&mut instructions, term, Register::T, clean);
+                emit(&mut instructions, Op::Or, Register::T, Register::J);
+                clean = false;
+            }
+        }
+
+        let script = Script { instructions, mode };
+        script.check()?;
+        Ok(script)
+    }
+
+    pub fn compile(expression: &str, mode: Mode) -> Result<Script, ScriptError> {
+        Script::from_terms(&Expr::parse(expression)?.terms(), mode)
+    }
+
+    /// Makes sure the droid will accept the script.
+    pub fn check(&self) -> Result<(), ScriptError> {
+        let count = self.instructions.len();
+        if count > MAX_INSTRUCTIONS {
+            return Err(ScriptError::TooLong { count });
+        }
+
+        for instruction in &self.instructions {
+            if let Register::Sensor(index) = instruction.source {
+                if index as usize >= self.mode.sensors() {
+                    return Err(ScriptError::SensorOutOfRange {
+                        sensor: (b'A' + index) as char,
+                        mode: self.mode,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The script as the droid reads it, one instruction per line and ending
+/// with the mode.
+impl fmt::Display for Script {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(formatter, "{}", instruction)?;
+        }
+
+        writeln!(formatter, "{}", self.mode)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Report {
+    /// The droid made it across and reported the hull damage.
+    Damage(i64),
+
+    /// The droid fell into space, and this is what it showed of its fall.
+    Fell(String),
+}
+
+/// Runs the droid's program with a script streamed in as ASCII.
+pub fn run<M: IntcodeMachine>(droid: &mut M, script: &Script) -> Result<Report, ScriptError> {
+    let mut text = String::new();
+    let mut damage = None;
+    let mut sent = false;
+
+    loop {
+        match droid.run() {
+            Outcome::Terminated => break,
+            Outcome::NeedsInput => {
+                if sent {
+                    return Err(ScriptError::Starved);
+                }
+
+                for byte in script.to_string().bytes() {
+                    droid.push_input(M::Word::from(byte as i32));
+                }
+
+                sent = true;
+            }
+            Outcome::SentOutput | Outcome::Paused => {
+                for value in droid.drain_output() {
+                    match value.to_i64() {
+                        Some(code @ 0..=127) => text.push(code as u8 as char),
+                        other => damage = other,
+                    }
+                }
+            }
+            Outcome::Error(error) => return Err(ScriptError::Fault(error)),
+        }
+    }
+
+    match damage {
+        Some(damage) => Ok(Report::Damage(damage)),
+        None => Ok(Report::Fell(text)),
+    }
+}
+
+/// Finds the hull in the droid's last words: the first row of `#` and `.`
+/// that isn't all air. True means ground, and the droid starts at index 0.
+pub fn read_hull(frame: &str) -> Option<Vec<bool>> {
+    frame
+        .lines()
+        .map(str::trim)
+        .find(|line| line.contains('#') && line.chars().all(|c| c == '#' || c == '.'))
+        .map(|line| line.chars().map(|c| c == '#').collect())
+}
+
+/// Which sensors see ground with the droid at `position`. Anything past the
+/// end of the hull is taken to be ground.
+fn sense(hull: &[bool], position: usize, mode: Mode) -> u16 {
+    (0..mode.sensors())
+        .filter(|index| hull.get(position + index + 1).copied().unwrap_or(true))
+        .fold(0, |state, index| state | 1 << index)
+}
+
+/// Walks the droid over a hull using the decisions made so far. Returns
+/// `Ok(true)` if it got across, `Ok(false)` if it fell, or the first state
+/// with no decision yet.
+fn simulate(hull: &[bool], decisions: &HashMap<u16, bool>, mode: Mode) -> Result<bool, u16> {
+    let mut position = 0;
+
+    while position < hull.len() {
+        let state = sense(hull, position, mode);
+
+        position += match decisions.get(&state) {
+            Some(true) => 4,
+            Some(false) => 1,
+            None => return Err(state),
+        };
+
+        if !hull.get(position).copied().unwrap_or(true) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Tries every set of decisions that gets across all the hulls, preferring
+/// to walk, until `accept` likes one.
+fn decide<T>(
+    hulls: &[Vec<bool>],
+    mode: Mode,
+    decisions: &mut HashMap<u16, bool>,
+    accept: &mut dyn FnMut(&HashMap<u16, bool>) -> Option<T>,
+) -> Option<T> {
+    for hull in hulls {
+        match simulate(hull, decisions, mode) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(state) => {
+                for &jump in &[false, true] {
+                    decisions.insert(state, jump);
+
+                    if let Some(found) = decide(hulls, mode, decisions, accept) {
+                        return Some(found);
+                    }
+                }
+
+                decisions.remove(&state);
+                return None;
+            }
+        }
+    }
+
+    accept(decisions)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Implicant {
+    value: u16,
+
+    /// Sensors this implicant doesn't care about.
+    mask: u16,
+}
+
+/// Turns jump decisions into a small OR of ANDs, Quine-McCluskey style.
+/// Sensor states without a decision can go either way.
+fn minimize(decisions: &HashMap<u16, bool>, mode: Mode) -> Vec<Term> {
+    let all = (1u16 << mode.sensors()) - 1;
+    let jumps: Vec<u16> = (0..=all)
+        .filter(|state| decisions.get(state) == Some(&true))
+        .collect();
+
+    let mut level: HashSet<Implicant> = (0..=all)
+        .filter(|state| decisions.get(state) != Some(&false))
+        .map(|value| Implicant { value, mask: 0 })
+        .collect();
+    let mut primes = Vec::new();
+
+    while !level.is_empty() {
+        let mut next = HashSet::new();
+        let mut merged = HashSet::new();
+
+        for &implicant in &level {
+            for bit in (0..mode.sensors()).map(|index| 1u16 << index) {
+                if implicant.mask & bit != 0 || implicant.value & bit != 0 {
+                    continue;
+                }
+
+                let partner = Implicant {
+                    value: implicant.value | bit,
+                    mask: implicant.mask,
+                };
+
+                if level.contains(&partner) {
+                    merged.insert(implicant);
+                    merged.insert(partner);
+                    next.insert(Implicant {
+                        value: implicant.value,
+                        mask: implicant.mask | bit,
+                    });
+                }
+            }
+        }
+
+        primes.extend(level.difference(&merged).copied());
+        level = next;
+    }
+
+    let primes: Vec<Term> = primes
+        .into_iter()
+        .map(|prime| {
+            let cared = all & !prime.mask;
+            Term {
+                ground: prime.value & cared,
+                holes: !prime.value & cared,
+            }
+        })
+        .collect();
+
+    // cover every jump greedily, preferring terms that cover more and then
+    // terms with fewer sensors
+    let mut uncovered = jumps;
+    let mut terms = Vec::new();
+
+    while !uncovered.is_empty() {
+        let best = primes
+            .iter()
+            .copied()
+            .max_by_key(|term| {
+                let covered = uncovered
+                    .iter()
+                    .filter(|&&state| term.matches(state))
+                    .count();
+                let literals = (term.ground | term.holes).count_ones();
+                (covered, std::cmp::Reverse(literals))
+            })
+            .unwrap();
+
+        uncovered.retain(|&state| !best.matches(state));
+        terms.push(best);
+    }
+
+    terms
+}
+
+/// Finds a script that gets the droid across, by simulating it over every
+/// hull it has fallen through so far and trying the result for real. Each
+/// attempt runs on a fresh copy of `droid`.
+pub fn search<M: IntcodeMachine + Clone>(
+    droid: &M,
+    mode: Mode,
+    attempts: usize,
+) -> Result<(Script, i64), ScriptError> {
+    let mut hulls: Vec<Vec<bool>> = Vec::new();
+
+    for _ in 0..attempts {
+        let script = decide(&hulls, mode, &mut HashMap::new(), &mut |decisions| {
+            Script::from_terms(&minimize(decisions, mode), mode).ok()
+        })
+        .ok_or(ScriptError::Unsolvable)?;
+
+        match run(&mut droid.clone(), &script)? {
+            Report::Damage(damage) => return Ok((script, damage)),
+            Report::Fell(frame) => {
+                let hull = read_hull(&frame).ok_or(ScriptError::UnreadableFrame)?;
+
+                if hulls.contains(&hull) {
+                    // the droid saw more than it showed us
+                    return Err(ScriptError::UnreadableFrame);
+                }
+
+                hulls.push(hull);
+            }
+        }
+    }
+
+    Err(ScriptError::GaveUp { attempts })
+}
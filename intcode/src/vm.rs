@@ -0,0 +1,609 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    mem,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    custom::CustomOp,
+    hooks::{Fuel, Hook, Hooks},
+    machine::IntcodeMachine,
+    word::Word,
+};
+
+pub(crate) const OP_ADD: u8 = 1;
+pub(crate) const OP_MUL: u8 = 2;
+pub(crate) const OP_INN: u8 = 3;
+pub(crate) const OP_OUT: u8 = 4;
+pub(crate) const OP_JIT: u8 = 5; // jump if true
+pub(crate) const OP_JIF: u8 = 6; // jump if false
+pub(crate) const OP_CML: u8 = 7; // compare less
+pub(crate) const OP_CME: u8 = 8; // compare equal
+pub(crate) const OP_ARB: u8 = 9; // add to relative base
+pub(crate) const OP_END: u8 = 99;
+
+pub(crate) const MODE_POS: u8 = 0;
+pub(crate) const MODE_IMM: u8 = 1;
+pub(crate) const MODE_REL: u8 = 2;
+
+/// Cloning a VM forks it, so both copies carry on independently from the
+/// same state. A hook is shared between the copies rather than cloned.
+#[derive(Default, Clone)]
+pub struct VM<W> {
+    pub pc: usize,
+    pub pc_checkpoint: usize,
+    pub rb: i64,
+    pub memory: Vec<W>,
+    pub input: VecDeque<W>,
+    pub output: VecDeque<W>,
+    pub arithmetic: Arithmetic,
+
+    /// Stores at or past this address stop the VM with
+    /// `Error::OutOfMemory` instead of growing memory to fit.
+    pub memory_limit: Option<usize>,
+
+    /// When set, the VM remembers which cells it has decoded as part of an
+    /// instruction and records any later write to one of them in
+    /// `self_modifications`.
+    pub detect_self_modification: bool,
+    pub self_modifications: Vec<SelfModification<W>>,
+    executed: Vec<Option<CellKind>>,
+
+    custom_ops: HashMap<u8, Arc<CustomOp<W>>>,
+    hooks: Hooks<W>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Outcome {
+    Terminated,
+    NeedsInput,
+    SentOutput,
+    Error(Error),
+
+    /// A hook asked the VM to stop. Calling `run_partial` again resumes it.
+    Paused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// An add or multiply didn't fit in the word under `Arithmetic::Checked`.
+    Overflow { pc: usize },
+
+    /// A pointer, jump target or relative base resolved to a negative or
+    /// unaddressable location.
+    InvalidAddress { pc: usize },
+
+    /// A store went past `VM::memory_limit`.
+    OutOfMemory { pc: usize },
+
+    /// The opcode at pc isn't one the VM knows.
+    IllegalInstruction { pc: usize },
+
+    /// A parameter used a mode that doesn't exist, or wrote through an
+    /// immediate.
+    IllegalMode { pc: usize, mode: u8 },
+}
+
+/// What `OP_ADD` and `OP_MUL` do when their result doesn't fit in a word.
+///
+/// Plain `+` and `*` panic in debug builds and wrap in release builds, so the
+/// policy is always explicit to keep results the same across profiles.
+/// `BigInt` words never overflow, so for programs that need exact answers
+/// however big they get, use a `VM<BigInt>` and the policy never applies.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Stop the VM with `Outcome::Error`, leaving pc on the instruction.
+    #[default]
+    Checked,
+    Wrapping,
+    Saturating,
+}
+
+/// How a cell was used the last time the VM decoded an instruction from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Opcode,
+    Operand,
+}
+
+/// A store that landed on a cell that had already been executed, which means
+/// the tape rewrites its own code and can't be safely cached or compiled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfModification<W> {
+    /// Address of the instruction that performed the write.
+    pub pc: usize,
+    pub target: usize,
+    pub kind: CellKind,
+    pub value: W,
+}
+
+impl<W: Word> VM<W> {
+    pub fn load_memory(&mut self, memory: impl Into<Vec<W>>) {
+        self.memory = memory.into();
+    }
+
+    pub fn load_memory_from_tape(&mut self, tape: &str) {
+        let memory = Self::decode_tape(tape);
+        self.load_memory(memory);
+    }
+
+    pub fn put_input(&mut self, value: W) {
+        self.input.push_back(value);
+    }
+
+    pub fn get_output(&mut self) -> W {
+        self.output.pop_front().unwrap()
+    }
+
+    /// Adds an instruction to this VM's dialect. Registered opcodes take
+    /// priority over the built-in ones, so they can also be used to
+    /// instrument or replace existing instructions.
+    ///
+    /// Panics if `op` is 100 or more, since only the last two digits of an
+    /// instruction are its opcode.
+    pub fn register_opcode(&mut self, op: u8, custom: CustomOp<W>) {
+        assert!(op < 100, "opcode {} doesn't fit in two digits", op);
+        self.custom_ops.insert(op, Arc::new(custom));
+    }
+
+    /// Attaches a hook that gets called as the VM runs, replacing any
+    /// previous one. The caller can keep its own handle to the hook to look
+    /// at what it collected.
+    pub fn set_hook<H: Hook<W> + 'static>(&mut self, hook: Arc<Mutex<H>>) {
+        self.hooks.hook = Some(hook);
+    }
+
+    /// Detaches the hook along with any watched address ranges.
+    pub fn clear_hook(&mut self) {
+        self.hooks = Hooks::default();
+    }
+
+    pub fn watch_reads(&mut self, range: Range<usize>) {
+        self.hooks.reads.push(range);
+    }
+
+    pub fn watch_writes(&mut self, range: Range<usize>) {
+        self.hooks.writes.push(range);
+    }
+
+    pub fn decode_tape(input: &str) -> Vec<W> {
+        input
+            .split(',')
+            .map(|v| {
+                v.parse()
+                    .unwrap_or_else(|_| panic!("invalid word {:?} in tape", v))
+            })
+            .collect()
+    }
+
+    fn load(&mut self, mode: u8, value: W) -> Result<W, Error> {
+        let ptr = match mode {
+            MODE_POS => self.address(&value)?,
+            MODE_IMM => return Ok(value),
+            MODE_REL => self.relative_address(&value)?,
+            _ => return Err(self.illegal_mode(mode)),
+        };
+
+        let value = self.read_ptr(ptr);
+
+        if let Some(hook) = self.hooks.watching_reads(ptr) {
+            self.hooks.pause_requested |= hook.lock().unwrap().on_read(ptr, &value);
+        }
+
+        Ok(value)
+    }
+
+    fn read_ptr(&self, ptr: usize) -> W {
+        self.memory.get(ptr).cloned().unwrap_or_else(|| W::from(0))
+    }
+
+    fn address(&self, value: &W) -> Result<usize, Error> {
+        value.to_address().ok_or(Error::InvalidAddress {
+            pc: self.pc_checkpoint,
+        })
+    }
+
+    fn relative_address(&self, offset: &W) -> Result<usize, Error> {
+        offset
+            .to_i64()
+            .and_then(|offset| self.rb.checked_add(offset))
+            .and_then(|ptr| usize::try_from(ptr).ok())
+            .ok_or(Error::InvalidAddress {
+                pc: self.pc_checkpoint,
+            })
+    }
+
+    fn pointer(&self, ptr: W, ptr_mode: u8) -> Result<usize, Error> {
+        let ptr = match ptr_mode {
+            MODE_POS => self.address(&ptr)?,
+            MODE_REL => self.relative_address(&ptr)?,
+            _ => return Err(self.illegal_mode(ptr_mode)),
+        };
+
+        match self.memory_limit {
+            Some(limit) if ptr >= limit => Err(Error::OutOfMemory {
+                pc: self.pc_checkpoint,
+            }),
+            _ => Ok(ptr),
+        }
+    }
+
+    fn store(&mut self, ptr: W, ptr_mode: u8, value: W) -> Result<(), Error> {
+        let ptr = self.pointer(ptr, ptr_mode)?;
+        self.write_ptr(ptr, value);
+        Ok(())
+    }
+
+    fn write_ptr(&mut self, ptr: usize, value: W) {
+        if ptr >= self.memory.len() {
+            self.memory.resize(ptr + 1, W::from(0));
+        }
+
+        if let Some(hook) = self.hooks.watching_writes(ptr) {
+            let old = &self.memory[ptr];
+            self.hooks.pause_requested |= hook.lock().unwrap().on_write(ptr, old, &value);
+        }
+
+        if self.detect_self_modification {
+            if let Some(&Some(kind)) = self.executed.get(ptr) {
+                self.self_modifications.push(SelfModification {
+                    pc: self.pc_checkpoint,
+                    target: ptr,
+                    kind,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        self.memory[ptr] = value;
+    }
+
+    fn arg_value(&mut self, mode: u8) -> Result<W, Error> {
+        let value = self.fetch(CellKind::Operand);
+        self.load(mode, value)
+    }
+
+    fn arg_raw(&mut self) -> W {
+        self.fetch(CellKind::Operand)
+    }
+
+    fn fetch(&mut self, kind: CellKind) -> W {
+        if self.detect_self_modification {
+            self.mark_executed(self.pc, kind);
+        }
+
+        let value = self.read_ptr(self.pc);
+        self.pc += 1;
+        value
+    }
+
+    fn mark_executed(&mut self, ptr: usize, kind: CellKind) {
+        if ptr >= self.executed.len() {
+            self.executed.resize(ptr + 1, None);
+        }
+
+        // a cell that has ever been run as an opcode stays marked as one
+        let cell = &mut self.executed[ptr];
+        if *cell != Some(CellKind::Opcode) {
+            *cell = Some(kind);
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        self.pc_checkpoint = self.pc;
+    }
+
+    fn rewind(&mut self) {
+        self.pc = self.pc_checkpoint;
+    }
+
+    fn overflow(&self) -> Error {
+        Error::Overflow {
+            pc: self.pc_checkpoint,
+        }
+    }
+
+    fn illegal_mode(&self, mode: u8) -> Error {
+        Error::IllegalMode {
+            pc: self.pc_checkpoint,
+            mode,
+        }
+    }
+
+    /// Runs until the program halts. Panics if it faults, runs out of input,
+    /// or pauses, like when its fuel runs out.
+    pub fn run_until_terminated(&mut self) {
+        loop {
+            match self.run_partial() {
+                Outcome::Terminated => break,
+                Outcome::NeedsInput => panic!("System starved for input"),
+                Outcome::SentOutput => {}
+                Outcome::Error(error) => panic!("VM faulted: {:?}", error),
+                Outcome::Paused => panic!("VM paused before the end"),
+            }
+        }
+    }
+
+    pub fn run_partial(&mut self) -> Outcome {
+        // left over from an instruction that had to hand back output first
+        if mem::take(&mut self.hooks.pause_requested) {
+            return Outcome::Paused;
+        }
+
+        loop {
+            self.checkpoint();
+
+            let result = self.step();
+            let pause_requested = mem::take(&mut self.hooks.pause_requested);
+
+            match result {
+                Ok(None) if pause_requested => return Outcome::Paused,
+                Ok(None) => {}
+                Ok(Some(Outcome::SentOutput)) => {
+                    self.hooks.pause_requested = pause_requested;
+                    return Outcome::SentOutput;
+                }
+                Ok(Some(Outcome::NeedsInput)) => {
+                    // the hook has already seen this instruction, so it
+                    // shouldn't get to break on it again once input arrives
+                    self.hooks.resuming = true;
+                    return Outcome::NeedsInput;
+                }
+                Ok(Some(outcome)) => return outcome,
+                Err(error) => {
+                    self.rewind();
+                    return Outcome::Error(error);
+                }
+            }
+        }
+    }
+
+    /// Executes the instruction at pc. Returns an outcome if the VM needs to
+    /// hand control back to its caller.
+    fn step(&mut self) -> Result<Option<Outcome>, Error> {
+        if !mem::take(&mut self.hooks.resuming) {
+            if let Some(hook) = self.hooks.hook.clone() {
+                if hook.lock().unwrap().before_instruction(self) {
+                    self.hooks.resuming = true;
+                    return Ok(Some(Outcome::Paused));
+                }
+            }
+        }
+
+        let inst = self.fetch(CellKind::Opcode);
+        let illegal = Error::IllegalInstruction {
+            pc: self.pc_checkpoint,
+        };
+        let inst = inst.to_i64().ok_or(illegal)?;
+        let (op, mode1, mode2, mode3) = decode_instruction(inst).ok_or(illegal)?;
+
+        // the handler needs the whole VM, so hold the op by its own handle
+        if let Some(custom) = self.custom_ops.get(&op).map(Arc::clone) {
+            return self.step_custom(inst, &custom);
+        }
+
+        match op {
+            OP_ADD => {
+                let a = self.arg_value(mode1)?;
+                let b = self.arg_value(mode2)?;
+                let out = self.arg_raw();
+
+                let value = a.add(&b, self.arithmetic).ok_or_else(|| self.overflow())?;
+                self.store(out, mode3, value)?;
+            }
+            OP_MUL => {
+                let a = self.arg_value(mode1)?;
+                let b = self.arg_value(mode2)?;
+                let out = self.arg_raw();
+
+                let value = a.mul(&b, self.arithmetic).ok_or_else(|| self.overflow())?;
+                self.store(out, mode3, value)?;
+            }
+            OP_INN => {
+                // resolve the destination first, so a bad one doesn't use up
+                // the input
+                let out = self.arg_raw();
+                let ptr = self.pointer(out, mode1)?;
+
+                match self.input.pop_front() {
+                    Some(value) => {
+                        if let Some(hook) = &self.hooks.hook {
+                            self.hooks.pause_requested |= hook.lock().unwrap().on_input(&value);
+                        }
+
+                        self.write_ptr(ptr, value);
+                    }
+                    None => {
+                        self.rewind();
+                        return Ok(Some(Outcome::NeedsInput));
+                    }
+                }
+            }
+            OP_OUT => {
+                let a = self.arg_value(mode1)?;
+
+                if let Some(hook) = &self.hooks.hook {
+                    // the VM is about to hand control back anyway
+                    hook.lock().unwrap().on_output(&a);
+                }
+
+                self.output.push_back(a);
+                return Ok(Some(Outcome::SentOutput));
+            }
+            OP_JIT => {
+                let cond = self.arg_value(mode1)?;
+                let dest = self.arg_value(mode2)?;
+
+                if !cond.is_zero() {
+                    self.pc = self.address(&dest)?;
+                }
+            }
+            OP_JIF => {
+                let cond = self.arg_value(mode1)?;
+                let dest = self.arg_value(mode2)?;
+
+                if cond.is_zero() {
+                    self.pc = self.address(&dest)?;
+                }
+            }
+            OP_CML => {
+                let a = self.arg_value(mode1)?;
+                let b = self.arg_value(mode2)?;
+                let out = self.arg_raw();
+
+                if a < b {
+                    self.store(out, mode3, W::from(1))?;
+                } else {
+                    self.store(out, mode3, W::from(0))?;
+                }
+            }
+            OP_CME => {
+                let a = self.arg_value(mode1)?;
+                let b = self.arg_value(mode2)?;
+                let out = self.arg_raw();
+
+                if a == b {
+                    self.store(out, mode3, W::from(1))?;
+                } else {
+                    self.store(out, mode3, W::from(0))?;
+                }
+            }
+            OP_ARB => {
+                let adjust = self.arg_value(mode1)?;
+
+                let rb = adjust
+                    .to_i64()
+                    .and_then(|adjust| self.rb.checked_add(adjust))
+                    .ok_or(Error::InvalidAddress {
+                        pc: self.pc_checkpoint,
+                    })?;
+
+                if let Some(hook) = &self.hooks.hook {
+                    self.hooks.pause_requested |= hook.lock().unwrap().on_rb_change(self.rb, rb);
+                }
+
+                self.rb = rb;
+            }
+            OP_END => {
+                self.rewind();
+                return Ok(Some(Outcome::Terminated));
+            }
+            _ => {
+                return Err(Error::IllegalInstruction {
+                    pc: self.pc_checkpoint,
+                })
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn step_custom(&mut self, inst: i64, custom: &CustomOp<W>) -> Result<Option<Outcome>, Error> {
+        let mut args = Vec::with_capacity(custom.arity);
+        let mut targets = Vec::with_capacity(custom.writes.len());
+
+        for index in 0..custom.arity {
+            let mode = operand_mode(inst, index);
+
+            if custom.writes.contains(&index) {
+                let ptr = self.arg_raw();
+                let target = self.pointer(ptr, mode)?;
+
+                args.push(self.read_ptr(target));
+                targets.push((index, target));
+            } else {
+                args.push(self.arg_value(mode)?);
+            }
+        }
+
+        let outcome = (custom.handler)(self, &mut args);
+
+        match outcome {
+            Some(Outcome::NeedsInput) | Some(Outcome::Terminated) | Some(Outcome::Error(_)) => {
+                self.rewind();
+            }
+            _ => {
+                for (index, target) in targets {
+                    self.write_ptr(target, args[index].clone());
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+impl<W: Word> IntcodeMachine for VM<W> {
+    type Word = W;
+
+    fn load(&mut self, program: &[W]) {
+        self.load_memory(program);
+        self.pc = 0;
+        self.pc_checkpoint = 0;
+        self.rb = 0;
+        self.input.clear();
+        self.output.clear();
+        self.self_modifications.clear();
+        self.executed.clear();
+        self.hooks.pause_requested = false;
+        self.hooks.resuming = false;
+    }
+
+    fn push_input(&mut self, value: W) {
+        self.put_input(value);
+    }
+
+    fn drain_output(&mut self) -> Vec<W> {
+        self.output.drain(..).collect()
+    }
+
+    fn run(&mut self) -> Outcome {
+        self.run_partial()
+    }
+
+    fn memory(&self) -> &[W] {
+        &self.memory
+    }
+
+    /// Attaches a fresh `Fuel` hook, replacing any other hook, or detaches
+    /// the hook with `None`. Like any hook, clones of the VM share it.
+    fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.hooks.hook = match fuel {
+            Some(remaining) => Some(Arc::new(Mutex::new(Fuel { remaining }))),
+            None => None,
+        };
+    }
+}
+
+/// Mode digit of the operand at `index`, counting from zero. Lets custom
+/// instructions take more than the three operands `decode_instruction` reads.
+fn operand_mode(instruction: i64, index: usize) -> u8 {
+    10i64
+        .checked_pow(index as u32 + 2)
+        .map(|place| (instruction / place % 10) as u8)
+        .unwrap_or(MODE_POS)
+}
+
+/// Splits an instruction into its opcode and three modes. Negative
+/// instructions have no digits to split, so they're `None`.
+pub(crate) fn decode_instruction(instruction: i64) -> Option<(u8, u8, u8, u8)> {
+    if instruction < 0 {
+        return None;
+    }
+
+    let mut remaining = instruction;
+    let op = instruction % 100;
+    remaining /= 100;
+
+    let mode1 = remaining % 10;
+    remaining /= 10;
+
+    let mode2 = remaining % 10;
+    remaining /= 10;
+
+    let mode3 = remaining % 10;
+
+    Some((op as u8, mode1 as u8, mode2 as u8, mode3 as u8))
+}
@@ -0,0 +1,102 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::vm::Arithmetic;
+
+/// A value that can live in a cell of Intcode memory.
+///
+/// Day 5 and day 7 got away with `i32`, day 9 needed `i64`, and some
+/// generated programs go past that, so the VM is generic over its word.
+pub trait Word:
+    Clone + Default + PartialEq + PartialOrd + From<i32> + FromStr + fmt::Debug + fmt::Display
+{
+    /// `None` if the value doesn't fit in this word.
+    fn from_i64(value: i64) -> Option<Self>;
+
+    fn is_zero(&self) -> bool;
+
+    /// The value as an `i64`, used to decode instructions and to offset the
+    /// relative base. `None` if it doesn't fit.
+    fn to_i64(&self) -> Option<i64>;
+
+    /// The value as a memory address. `None` for negative values and values
+    /// too big to index with.
+    fn to_address(&self) -> Option<usize>;
+
+    /// `None` means the result overflowed under `Arithmetic::Checked`.
+    fn add(&self, other: &Self, arithmetic: Arithmetic) -> Option<Self>;
+
+    /// `None` means the result overflowed under `Arithmetic::Checked`.
+    fn mul(&self, other: &Self, arithmetic: Arithmetic) -> Option<Self>;
+}
+
+macro_rules! primitive_word {
+    ($ty:ty) => {
+        impl Word for $ty {
+            fn from_i64(value: i64) -> Option<Self> {
+                <$ty>::try_from(value).ok()
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+
+            fn to_i64(&self) -> Option<i64> {
+                i64::try_from(*self).ok()
+            }
+
+            fn to_address(&self) -> Option<usize> {
+                usize::try_from(*self).ok()
+            }
+
+            fn add(&self, other: &Self, arithmetic: Arithmetic) -> Option<Self> {
+                match arithmetic {
+                    Arithmetic::Checked => self.checked_add(*other),
+                    Arithmetic::Wrapping => Some(self.wrapping_add(*other)),
+                    Arithmetic::Saturating => Some(self.saturating_add(*other)),
+                }
+            }
+
+            fn mul(&self, other: &Self, arithmetic: Arithmetic) -> Option<Self> {
+                match arithmetic {
+                    Arithmetic::Checked => self.checked_mul(*other),
+                    Arithmetic::Wrapping => Some(self.wrapping_mul(*other)),
+                    Arithmetic::Saturating => Some(self.saturating_mul(*other)),
+                }
+            }
+        }
+    };
+}
+
+primitive_word!(i32);
+primitive_word!(i64);
+primitive_word!(i128);
+
+// big integers can't overflow, so every arithmetic policy is exact
+impl Word for BigInt {
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(BigInt::from(value))
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        ToPrimitive::to_i64(self)
+    }
+
+    fn to_address(&self) -> Option<usize> {
+        self.to_usize()
+    }
+
+    fn add(&self, other: &Self, _arithmetic: Arithmetic) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn mul(&self, other: &Self, _arithmetic: Arithmetic) -> Option<Self> {
+        Some(self * other)
+    }
+}
@@ -0,0 +1,19 @@
+; mul_loop(a, b) -> a * b
+;
+; Multiplies by repeated addition, for dialects or experiments without
+; `mul`. Returns 0 when b isn't positive.
+
+.export mul_loop
+
+; [rb+1] a, [rb+2] b, [rb+3] total, [rb+4] scratch
+mul_loop:
+    mov 0, [rb+3]
+.loop:
+    cml 0, [rb+2], [rb+4]
+    jif [rb+4], .done
+    add [rb+3], [rb+1], [rb+3]
+    add [rb+2], -1, [rb+2]
+    jmp .loop
+.done:
+    mov [rb+3], [rb+1]
+    ret
@@ -0,0 +1,47 @@
+; print_number(n)
+;
+; Prints n as decimal ASCII, with a leading '-' if it's negative. Numbers of
+; 10^18 or more overflow while working out how many digits there are.
+
+.export print_number
+
+; [rb+1] n, [rb+2] scratch
+print_number:
+    cml [rb+1], 0, [rb+2]
+    jif [rb+2], .positive
+    out '-'
+    mul [rb+1], -1, [rb+1]
+.positive:
+    mov [rb+1], [rb+4]
+    mov 1, [rb+5]
+    call print_digits, 3
+    ret
+
+; print_digits(n, place) -> n mod place
+;
+; Prints the digits of n from `place` upwards. There's no division, so this
+; recurses up to the leading digit and counts each digit out by subtraction
+; on the way back down.
+;
+; [rb+1] n, [rb+2] place, [rb+3] scratch, [rb+4] digit, [rb+5] next place
+print_digits:
+    mul [rb+2], 10, [rb+5]
+    cml [rb+1], [rb+5], [rb+3]
+    jit [rb+3], .count
+    mov [rb+1], [rb+7]
+    mov [rb+5], [rb+8]
+    call print_digits, 6
+    mov [rb+7], [rb+1]
+.count:
+    mov 0, [rb+4]
+.subtract:
+    cml [rb+1], [rb+2], [rb+3]
+    jit [rb+3], .print
+    mul [rb+2], -1, [rb+3]
+    add [rb+1], [rb+3], [rb+1]
+    add [rb+4], 1, [rb+4]
+    jmp .subtract
+.print:
+    add [rb+4], '0', [rb+4]
+    out [rb+4]
+    ret
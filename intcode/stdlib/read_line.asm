@@ -0,0 +1,24 @@
+; read_line(buffer) -> length
+;
+; Reads characters into memory starting at `buffer` until a newline, which
+; isn't stored.
+
+.export read_line
+
+; [rb+1] buffer, [rb+2] pointer, [rb+3] character, [rb+4] scratch
+read_line:
+    mov [rb+1], [rb+2]
+.next:
+    inn [rb+3]
+    cme [rb+3], '\n', [rb+4]
+    jit [rb+4], .done
+    ; there's no way to store through a pointer, so patch it into the store
+    mov [rb+2], [.store+3]
+.store:
+    mov [rb+3], [0]
+    add [rb+2], 1, [rb+2]
+    jmp .next
+.done:
+    mul [rb+1], -1, [rb+4]
+    add [rb+2], [rb+4], [rb+1]
+    ret
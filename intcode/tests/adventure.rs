@@ -0,0 +1,302 @@
+mod common;
+
+use common::{Fake, Puzzle};
+use intcode::adventure::{
+    crack, explore, parse_rooms, password, play, AdventureError, Game, Room, MAX_ITEMS,
+};
+use intcode::{asm::assemble, IntcodeMachine, Outcome, VM};
+
+/// A room's name, where its doors go, and what's lying around in it.
+type FakeRoom = (
+    &'static str,
+    &'static [(&'static str, &'static str)],
+    &'static [&'static str],
+);
+
+static ROOMS: &[FakeRoom] = &[
+    (
+        "Hull Breach",
+        &[("north", "Kitchen"), ("east", "Security Checkpoint")],
+        &[],
+    ),
+    (
+        "Kitchen",
+        &[("south", "Hull Breach"), ("west", "Storage")],
+        &["mug", "molten lava"],
+    ),
+    (
+        "Storage",
+        &[("east", "Kitchen")],
+        &["fuel cell", "space heater", "infinite loop"],
+    ),
+    (
+        "Security Checkpoint",
+        &[
+            ("west", "Hull Breach"),
+            ("south", "Pressure-Sensitive Floor"),
+        ],
+        &[],
+    ),
+    (
+        "Pressure-Sensitive Floor",
+        &[("north", "Security Checkpoint")],
+        &[],
+    ),
+];
+
+fn weight(item: &str) -> u32 {
+    match item {
+        "mug" => 1,
+        "fuel cell" => 2,
+        "space heater" => 4,
+        _ => 0,
+    }
+}
+
+/// The floor wants the mug and the fuel cell and nothing else.
+const TARGET: u32 = 3;
+
+/// Plays the adventure in `ROOMS` over ASCII, like the real game.
+#[derive(Clone)]
+struct FakeGame {
+    room: usize,
+    items: Vec<Vec<&'static str>>,
+    carrying: Vec<&'static str>,
+    started: bool,
+    looping: bool,
+    finished: bool,
+    input: String,
+    output: Vec<i64>,
+}
+
+impl FakeGame {
+    fn new() -> Self {
+        FakeGame {
+            room: 0,
+            items: ROOMS.iter().map(|(_, _, items)| items.to_vec()).collect(),
+            carrying: Vec::new(),
+            started: false,
+            looping: false,
+            finished: false,
+            input: String::new(),
+            output: Vec::new(),
+        }
+    }
+
+    fn say(&mut self, text: &str) {
+        self.output.extend(text.bytes().map(i64::from));
+    }
+
+    fn describe(&mut self, name: &str) {
+        let index = ROOMS.iter().position(|room| room.0 == name).unwrap();
+        let mut text = format!("\n\n\n== {} ==\nIt's a room.\n\nDoors here lead:\n", name);
+
+        for (door, _) in ROOMS[index].1 {
+            text += &format!("- {}\n", door);
+        }
+
+        if !self.items[index].is_empty() {
+            text += "\nItems here:\n";
+            for item in &self.items[index] {
+                text += &format!("- {}\n", item);
+            }
+        }
+
+        self.say(&text);
+    }
+
+    fn command(&mut self, command: &str) {
+        if let Some(item) = command.strip_prefix("take ") {
+            let items = &mut self.items[self.room];
+            let index = items.iter().position(|&other| other == item).unwrap();
+            let item = items.remove(index);
+
+            match item {
+                "molten lava" => {
+                    self.say("\nThe molten lava is way too hot! You melt!\n");
+                    self.finished = true;
+                    return;
+                }
+                "infinite loop" => {
+                    self.looping = true;
+                    return;
+                }
+                _ => {}
+            }
+
+            self.carrying.push(item);
+            self.say(&format!("\nYou take the {}.\n", item));
+        } else if let Some(item) = command.strip_prefix("drop ") {
+            let index = self
+                .carrying
+                .iter()
+                .position(|&other| other == item)
+                .unwrap();
+            let item = self.carrying.remove(index);
+            self.items[self.room].push(item);
+            self.say(&format!("\nYou drop the {}.\n", item));
+        } else {
+            let &(_, target) = ROOMS[self.room]
+                .1
+                .iter()
+                .find(|(door, _)| *door == command)
+                .unwrap();
+
+            if target == "Pressure-Sensitive Floor" {
+                self.describe(target);
+
+                let weight: u32 = self.carrying.iter().map(|item| weight(item)).sum();
+                if weight == TARGET {
+                    self.say("You may proceed. Type 20483 on the keypad.\n");
+                    self.finished = true;
+                    return;
+                }
+
+                self.say("Alert! You are ejected back to the checkpoint.\n");
+                self.describe("Security Checkpoint");
+            } else {
+                self.room = ROOMS.iter().position(|room| room.0 == target).unwrap();
+                self.describe(target);
+            }
+        }
+
+        self.say("\nCommand?\n");
+    }
+}
+
+impl Puzzle for FakeGame {
+    fn push_input(&mut self, value: i64) {
+        self.input.push(value as u8 as char);
+    }
+
+    fn drain_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn run(&mut self) -> Outcome {
+        if self.looping {
+            self.say("You take the infinite loop.\n");
+            return Outcome::SentOutput;
+        }
+
+        if !self.output.is_empty() {
+            return Outcome::SentOutput;
+        }
+
+        if self.finished {
+            return Outcome::Terminated;
+        }
+
+        if !self.started {
+            self.started = true;
+            self.describe("Hull Breach");
+            self.say("\nCommand?\n");
+            return Outcome::SentOutput;
+        }
+
+        match self.input.find('\n') {
+            Some(end) => {
+                let command: String = self.input.drain(..=end).collect();
+                self.command(command.trim_end());
+                self.run()
+            }
+            None => Outcome::NeedsInput,
+        }
+    }
+}
+
+#[test]
+fn parses_rooms() {
+    let text = "\n\n\n== Kitchen ==\nSmells like toast.\n\nDoors here lead:\n- south\n- west\n\nItems here:\n- mug\n\nCommand?\n";
+
+    assert_eq!(
+        parse_rooms(text),
+        vec![Room {
+            name: "Kitchen".to_owned(),
+            description: "Smells like toast.".to_owned(),
+            doors: vec!["south".to_owned(), "west".to_owned()],
+            items: vec!["mug".to_owned()],
+        }]
+    );
+
+    assert_eq!(
+        password("Oh, hello! You should be able to get in by typing 2424308736 on the keypad"),
+        Some("2424308736")
+    );
+}
+
+#[test]
+fn maps_ship() {
+    let game = Game::new(Fake(FakeGame::new())).unwrap();
+    let map = explore(&game).unwrap();
+
+    let names: Vec<_> = map.rooms.iter().map(|room| room.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["Hull Breach", "Kitchen", "Security Checkpoint", "Storage"]
+    );
+
+    let mut deadly: Vec<_> = map.deadly.iter().map(String::as_str).collect();
+    deadly.sort();
+    assert_eq!(deadly, vec!["infinite loop", "molten lava"]);
+
+    assert_eq!(
+        map.checkpoint,
+        Some(("Security Checkpoint".to_owned(), "south".to_owned()))
+    );
+    assert_eq!(
+        map.route("Storage", "Security Checkpoint"),
+        Some(vec![
+            "east".to_owned(),
+            "south".to_owned(),
+            "east".to_owned()
+        ])
+    );
+
+    // exploring only touched snapshots
+    assert_eq!(game.room.name, "Hull Breach");
+}
+
+#[test]
+fn plays_to_the_end() {
+    let text = play(Fake(FakeGame::new())).unwrap();
+
+    assert_eq!(password(&text), Some("20483"));
+}
+
+#[test]
+fn runaway_output() {
+    let mut game = Game::new(Fake(FakeGame::new())).unwrap();
+    game.send("north").unwrap();
+    game.send("west").unwrap();
+
+    assert_eq!(
+        game.send("take infinite loop"),
+        Err(AdventureError::Runaway)
+    );
+}
+
+#[test]
+fn runaway_loop() {
+    // says nothing at all, forever
+    let mut vm = VM::<i64>::default();
+    vm.load(&assemble("loop: jit 1, loop").unwrap().code);
+
+    assert!(matches!(Game::new(vm), Err(AdventureError::Runaway)));
+}
+
+#[test]
+fn too_many_items() {
+    let game = Game::new(Fake(FakeGame::new())).unwrap();
+    let map = explore(&game).unwrap();
+    let carrying: Vec<String> = (0..40).map(|index| format!("rock {}", index)).collect();
+
+    assert_eq!(
+        crack(&game, &map, &carrying),
+        Err(AdventureError::TooManyItems(40))
+    );
+    assert_eq!(
+        crack(&game, &map, &carrying[..MAX_ITEMS + 1]),
+        Err(AdventureError::TooManyItems(MAX_ITEMS + 1))
+    );
+}
@@ -0,0 +1,75 @@
+use intcode::{
+    arcade::{track_ball, ArcadeError, Cabinet, Joystick, Replay, Tile},
+    asm::assemble,
+    IntcodeMachine, VM,
+};
+
+/// Draws a wall, a paddle and a ball, then moves the paddle once by the
+/// joystick and sets the score to where the paddle ended up.
+static GAME: &str = "
+    out 0
+    out 0
+    out 1
+    out [x]
+    out 1
+    out 3
+    out 3
+    out 0
+    out 4
+    inn [joystick]
+    out [x]
+    out 1
+    out 0
+    add [x], [joystick], [x]
+    out [x]
+    out 1
+    out 3
+    out -1
+    out 0
+    out [x]
+    end
+
+x: data 1
+joystick: data 0
+";
+
+fn game() -> VM<i64> {
+    let mut vm = VM::default();
+    vm.load(&assemble(GAME).unwrap().code);
+    vm
+}
+
+#[test]
+fn auto_play() {
+    let mut cabinet = Cabinet::new(game());
+
+    assert_eq!(cabinet.play(track_ball), Ok(2));
+    assert_eq!(cabinet.moves, vec![Joystick::Right]);
+    assert_eq!(cabinet.screen.find(Tile::Paddle), Some((2, 1)));
+    assert_eq!(cabinet.screen.count(Tile::Wall), 1);
+    assert_eq!(cabinet.screen.render(), "#  o\n  = \nScore: 2\n");
+}
+
+#[test]
+fn replay() {
+    let frames: Vec<_> = Replay::new(game(), &[Joystick::Left])
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].find(Tile::Paddle), Some((1, 1)));
+    assert_eq!(frames[0].score, 0);
+    assert_eq!(frames[1].find(Tile::Paddle), Some((0, 1)));
+    assert_eq!(frames[1].score, 0);
+
+    // a recording that stops early just stops
+    assert_eq!(Replay::new(game(), &[]).count(), 1);
+}
+
+#[test]
+fn out_of_fuel() {
+    let mut vm = game();
+    vm.set_fuel(Some(3));
+
+    assert_eq!(Cabinet::new(vm).play(track_ball), Err(ArcadeError::Paused));
+}
@@ -0,0 +1,113 @@
+use intcode::{Arithmetic, BigInt, Error, IntcodeMachine, Outcome, Word, VM};
+
+/// add or mul two immediates into [7], then out [7].
+fn program(op: i64, a: i64, b: i64) -> Vec<i64> {
+    vec![1100 + op, a, b, 7, 4, 7, 99, 0]
+}
+
+fn run<W: Word>(arithmetic: Arithmetic, op: i64, a: i64, b: i64) -> Result<W, Error> {
+    let program: Vec<W> = program(op, a, b)
+        .into_iter()
+        .map(|value| W::from_i64(value).unwrap())
+        .collect();
+
+    let mut vm = VM::default();
+    vm.arithmetic = arithmetic;
+    vm.load(&program);
+
+    match vm.run() {
+        Outcome::SentOutput => Ok(vm.get_output()),
+        Outcome::Error(error) => Err(error),
+        _ => panic!("program didn't output"),
+    }
+}
+
+const ADD: i64 = 1;
+const MUL: i64 = 2;
+
+#[test]
+fn checked() {
+    let overflow = Error::Overflow { pc: 0 };
+
+    assert_eq!(
+        run::<i32>(Arithmetic::Checked, ADD, i32::MAX as i64, 1),
+        Err(overflow)
+    );
+    assert_eq!(
+        run::<i32>(Arithmetic::Checked, MUL, i32::MAX as i64, 2),
+        Err(overflow)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Checked, ADD, i64::MAX, 1),
+        Err(overflow)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Checked, MUL, i64::MAX, 2),
+        Err(overflow)
+    );
+
+    // right up to the edge is fine
+    assert_eq!(
+        run::<i32>(Arithmetic::Checked, ADD, i32::MAX as i64 - 1, 1),
+        Ok(i32::MAX)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Checked, MUL, i64::MAX, 1),
+        Ok(i64::MAX)
+    );
+}
+
+#[test]
+fn wrapping() {
+    assert_eq!(
+        run::<i32>(Arithmetic::Wrapping, ADD, i32::MAX as i64, 1),
+        Ok(i32::MIN)
+    );
+    assert_eq!(
+        run::<i32>(Arithmetic::Wrapping, MUL, i32::MAX as i64, 2),
+        Ok(-2)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Wrapping, ADD, i64::MAX, 1),
+        Ok(i64::MIN)
+    );
+    assert_eq!(run::<i64>(Arithmetic::Wrapping, MUL, i64::MAX, 2), Ok(-2));
+}
+
+#[test]
+fn saturating() {
+    assert_eq!(
+        run::<i32>(Arithmetic::Saturating, ADD, i32::MAX as i64, 1),
+        Ok(i32::MAX)
+    );
+    assert_eq!(
+        run::<i32>(Arithmetic::Saturating, MUL, i32::MAX as i64, -2),
+        Ok(i32::MIN)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Saturating, ADD, i64::MAX, 1),
+        Ok(i64::MAX)
+    );
+    assert_eq!(
+        run::<i64>(Arithmetic::Saturating, MUL, i64::MAX, 2),
+        Ok(i64::MAX)
+    );
+}
+
+#[test]
+fn big_integers_never_overflow() {
+    for &arithmetic in &[
+        Arithmetic::Checked,
+        Arithmetic::Wrapping,
+        Arithmetic::Saturating,
+    ] {
+        assert_eq!(
+            run::<BigInt>(arithmetic, ADD, i64::MAX, 1),
+            Ok(BigInt::from(i64::MAX) + 1)
+        );
+        assert_eq!(
+            run::<BigInt>(arithmetic, MUL, i64::MAX, 2),
+            Ok(BigInt::from(i64::MAX) * 2)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use intcode::{
+    asm::assemble,
+    circuit::{Circuit, CircuitError},
+    QueryOracle, VM,
+};
+
+/// Reads its phase as a count, then that many inputs, and sends their sum.
+static SUM: &str = "
+    inn [count]
+
+loop:
+    inn [x]
+    add [sum], [x], [sum]
+    add [count], -1, [count]
+    jit [count], loop
+    out [sum]
+    end
+
+count: data 0
+x: data 0
+sum: data 0
+";
+
+/// Ignores its phase and passes every input straight on, forever.
+static FORWARD: &str = "
+    inn [x]
+
+loop:
+    inn [x]
+    out [x]
+    jit 1, loop
+
+x: data 0
+";
+
+/// Never reads anything past its phase, and talks forever.
+static CHATTER: &str = "
+    inn [x]
+
+loop:
+    out 1
+    jit 1, loop
+
+x: data 0
+";
+
+/// Never reads anything past its phase, and never says anything either.
+static SPIN: &str = "
+    inn [x]
+
+loop:
+    jit 1, loop
+
+x: data 0
+";
+
+fn solve(circuit: &Circuit, source: &str, phases: &[i64]) -> Result<i64, CircuitError> {
+    circuit.solve(&assemble(source).unwrap().code, phases)
+}
+
+#[test]
+fn puzzle_examples() {
+    let chain = VM::decode_tape("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+    let ring = VM::decode_tape(
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+    );
+
+    assert_eq!(Circuit::chain(5).solve(&chain, &[4, 3, 2, 1, 0]), Ok(43210));
+    assert_eq!(
+        Circuit::ring(5).solve(&ring, &[9, 8, 7, 6, 5]),
+        Ok(139_629_729)
+    );
+
+    let mut oracle: QueryOracle<VM<i32>> = QueryOracle::from_tape(&chain);
+    assert_eq!(
+        Circuit::chain(5).solve_chain(&mut oracle, &[4, 3, 2, 1, 0]),
+        Ok(43210)
+    );
+    assert_eq!(
+        Circuit::ring(5).solve_chain(&mut oracle, &[9, 8, 7, 6, 5]),
+        Err(CircuitError::NotAChain)
+    );
+}
+
+#[test]
+fn finds_chains() {
+    assert_eq!(Circuit::chain(3).chain_order(), Some(vec![0, 1, 2]));
+    assert_eq!(Circuit::ring(3).chain_order(), None);
+
+    let backwards = Circuit::parse("nodes A B C\nC -> B\nB -> A\noutput A").unwrap();
+    assert_eq!(backwards.chain_order(), Some(vec![2, 1, 0]));
+
+    let fan_out = Circuit::parse("nodes A B C\nA -> B C\noutput C").unwrap();
+    assert_eq!(fan_out.chain_order(), None);
+
+    // the output has to be the end of the chain
+    let early = Circuit::parse("nodes A B C\nA -> B\nB -> C\noutput B").unwrap();
+    assert_eq!(early.chain_order(), None);
+}
+
+#[test]
+fn names_past_z() {
+    let circuit = Circuit::ring(40);
+
+    assert_eq!(circuit.nodes[25], "Z");
+    assert_eq!(circuit.nodes[26], "N26");
+    assert_eq!(circuit.nodes[39], "N39");
+}
+
+#[test]
+fn parses_like_builders() {
+    let ring = Circuit::parse(
+        "
+        # five amplifiers in a loop
+        nodes A B C D E
+        A -> B
+        B -> C
+        C -> D
+        D -> E
+        E -> A
+        input A 0
+        output E
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(ring, Circuit::ring(5));
+}
+
+#[test]
+fn fan_out_and_in() {
+    let circuit = Circuit::parse(
+        "
+        nodes A B C D
+        A -> B C
+        B -> D
+        C -> D
+        input A 5
+        input B 10
+        output D
+        ",
+    )
+    .unwrap();
+
+    // B adds its queued 10 to what A sends, and D adds up B and C
+    assert_eq!(solve(&circuit, SUM, &[1, 2, 1, 2]), Ok(20));
+}
+
+#[test]
+fn stuck_circuits() {
+    let starved = Circuit::parse("nodes A B\nA -> B\nB -> A\noutput B").unwrap();
+    assert_eq!(
+        solve(&starved, SUM, &[1, 1]),
+        Err(CircuitError::Deadlock {
+            waiting: vec!["A".to_owned(), "B".to_owned()]
+        })
+    );
+
+    assert_eq!(
+        solve(&Circuit::ring(2), FORWARD, &[0, 0]),
+        Err(CircuitError::Runaway)
+    );
+    assert_eq!(
+        solve(&Circuit::chain(2), CHATTER, &[0, 0]),
+        Err(CircuitError::Runaway)
+    );
+    assert_eq!(
+        solve(&Circuit::chain(2), SPIN, &[0, 0]),
+        Err(CircuitError::Runaway)
+    );
+
+    assert_eq!(
+        solve(&Circuit::chain(3), SUM, &[1, 1]),
+        Err(CircuitError::PhaseCount {
+            expected: 3,
+            found: 2
+        })
+    );
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        Circuit::parse("nodes A\nA -> B\noutput A"),
+        Err(CircuitError::UnknownNode {
+            line: 2,
+            name: "B".to_owned()
+        })
+    );
+    assert!(matches!(
+        Circuit::parse("nodes A B\nA => B\noutput B"),
+        Err(CircuitError::Parse { line: 2, .. })
+    ));
+    assert!(matches!(
+        Circuit::parse("nodes A\ninput A zero\noutput A"),
+        Err(CircuitError::Parse { line: 2, .. })
+    ));
+    assert!(matches!(
+        Circuit::parse("nodes A B\nA -> B"),
+        Err(CircuitError::Parse { .. })
+    ));
+}
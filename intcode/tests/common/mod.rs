@@ -0,0 +1,44 @@
+//! A test double for the puzzle harnesses, so they can be tested against
+//! the puzzle's side played in Rust instead of a real tape.
+
+use intcode::{IntcodeMachine, Outcome};
+
+/// The puzzle's side of the conversation, played in Rust.
+pub trait Puzzle {
+    fn push_input(&mut self, value: i64);
+
+    fn drain_output(&mut self) -> Vec<i64>;
+
+    /// Carries on from the last call, like `IntcodeMachine::run`.
+    fn run(&mut self) -> Outcome;
+}
+
+/// Runs a `Puzzle` as if it were an Intcode machine.
+#[derive(Clone, Default)]
+pub struct Fake<P>(pub P);
+
+impl<P: Puzzle> IntcodeMachine for Fake<P> {
+    type Word = i64;
+
+    /// The puzzle is the program, so there's nothing to load and this does
+    /// nothing. Harnesses that reload their machine get the puzzle as it
+    /// was.
+    fn load(&mut self, _program: &[i64]) {}
+
+    fn push_input(&mut self, value: i64) {
+        self.0.push_input(value);
+    }
+
+    fn drain_output(&mut self) -> Vec<i64> {
+        self.0.drain_output()
+    }
+
+    fn run(&mut self) -> Outcome {
+        self.0.run()
+    }
+
+    /// Puzzles don't have any memory to look at.
+    fn memory(&self) -> &[i64] {
+        &[]
+    }
+}
@@ -0,0 +1,123 @@
+use intcode::{conformance, BigInt, CachedVM, Error, IntcodeMachine, Outcome, VM};
+
+fn assert_conforms<M: IntcodeMachine>(make: impl Fn() -> M) {
+    let report = conformance::check(make);
+
+    for failure in &report.failures {
+        eprintln!("{}", failure);
+    }
+
+    assert!(
+        report.failures.is_empty(),
+        "{} of {} cases failed",
+        report.failures.len(),
+        report.passed + report.failures.len()
+    );
+}
+
+#[test]
+fn vm_i32() {
+    assert_conforms(VM::<i32>::default);
+}
+
+#[test]
+fn vm_i64() {
+    assert_conforms(VM::<i64>::default);
+}
+
+#[test]
+fn vm_i128() {
+    assert_conforms(VM::<i128>::default);
+}
+
+#[test]
+fn vm_bigint() {
+    assert_conforms(VM::<BigInt>::default);
+}
+
+#[test]
+fn cached_vm_i64() {
+    assert_conforms(CachedVM::<i64>::default);
+}
+
+#[test]
+fn cached_vm_bigint() {
+    assert_conforms(CachedVM::<BigInt>::default);
+}
+
+/// Outputs 1 forever.
+static CHATTER: &[i64] = &[104, 1, 1105, 1, 0];
+
+fn runs_out_of_fuel<M: IntcodeMachine<Word = i64>>(mut machine: M) {
+    machine.load(CHATTER);
+    machine.set_fuel(Some(30));
+
+    // a paused run isn't a finished one
+    machine.run_to_end();
+}
+
+#[test]
+#[should_panic(expected = "paused before the end")]
+fn run_to_end_refuses_to_pause_vm() {
+    runs_out_of_fuel(VM::default());
+}
+
+#[test]
+#[should_panic(expected = "paused before the end")]
+fn run_to_end_refuses_to_pause_cached_vm() {
+    runs_out_of_fuel(CachedVM::default());
+}
+
+#[test]
+#[should_panic(expected = "paused before the end")]
+fn run_until_terminated_refuses_to_pause() {
+    let mut vm = VM::default();
+    vm.load(CHATTER);
+    vm.set_fuel(Some(30));
+
+    vm.run_until_terminated();
+}
+
+/// inn [-1]; end
+static INN_NEGATIVE: &[i64] = &[3, -1, 99];
+
+/// inn [10]; end
+static INN_FAR: &[i64] = &[3, 10, 99];
+
+#[test]
+fn failed_input_stays_queued() {
+    let mut vm = VM::default();
+    vm.load(INN_NEGATIVE);
+    vm.push_input(7);
+    assert!(matches!(
+        vm.run(),
+        Outcome::Error(Error::InvalidAddress { pc: 0 })
+    ));
+    assert_eq!(vm.input, [7]);
+
+    vm.load(INN_FAR);
+    vm.memory_limit = Some(5);
+    vm.push_input(7);
+    assert!(matches!(
+        vm.run(),
+        Outcome::Error(Error::OutOfMemory { pc: 0 })
+    ));
+    assert_eq!(vm.input, [7]);
+
+    let mut cached = CachedVM::new(INN_NEGATIVE);
+    cached.push_input(7);
+    assert!(matches!(
+        cached.run(),
+        Outcome::Error(Error::InvalidAddress { pc: 0 })
+    ));
+    assert_eq!(cached.input, [7]);
+
+    let mut cached = CachedVM::new(INN_FAR);
+    cached.memory_limit = Some(5);
+    cached.push_input(7);
+    assert!(matches!(
+        cached.run(),
+        Outcome::Error(Error::OutOfMemory { pc: 0 })
+    ));
+    assert_eq!(cached.input, [7]);
+}
@@ -0,0 +1,118 @@
+use intcode::{CachedVM, CustomOp, Error, IntcodeMachine, Outcome, VM};
+
+const OP_SUB: u8 = 10;
+const OP_GOTO: u8 = 11;
+const OP_TAKE: u8 = 12;
+const OP_FAIL: u8 = 13;
+
+/// `sub a, b, out` stores a - b.
+fn sub() -> CustomOp<i64> {
+    CustomOp::new("sub", 3, &[2], |_vm, args| {
+        args[2] = args[0] - args[1];
+        None
+    })
+}
+
+/// `goto dest` always jumps.
+fn goto() -> CustomOp<i64> {
+    CustomOp::new("goto", 1, &[], |vm, args| {
+        vm.pc = args[0] as usize;
+        None
+    })
+}
+
+/// `take out` is like `inn`, but it scribbles over pc and its operand
+/// before asking for input, which should all be thrown away.
+fn take() -> CustomOp<i64> {
+    CustomOp::new("take", 1, &[0], |vm, args| match vm.input.pop_front() {
+        Some(value) => {
+            args[0] = value;
+            None
+        }
+        None => {
+            args[0] = -1;
+            vm.pc = 1000;
+            Some(Outcome::NeedsInput)
+        }
+    })
+}
+
+fn vm(program: &[i64]) -> VM<i64> {
+    let mut vm = VM::default();
+    vm.register_opcode(OP_SUB, sub());
+    vm.register_opcode(OP_GOTO, goto());
+    vm.register_opcode(OP_TAKE, take());
+    vm.register_opcode(
+        OP_FAIL,
+        CustomOp::new("fail", 0, &[], |vm, _args| {
+            vm.pc = 1000;
+            Some(Outcome::Error(Error::Overflow { pc: 0 }))
+        }),
+    );
+    vm.load(program);
+    vm
+}
+
+#[test]
+fn reads_and_writes() {
+    // sub 7, 3, [13]; out [13]; sub [13], 10, [13]; out [13]; end
+    let mut vm = vm(&[1110, 7, 3, 13, 4, 13, 1010, 13, 10, 13, 4, 13, 99, 0]);
+
+    assert_eq!(vm.run_to_end(), vec![4, -6]);
+}
+
+#[test]
+fn jumps() {
+    // goto 5; out 1; end; out 2; end
+    let mut vm = vm(&[111, 5, 104, 1, 99, 104, 2, 99]);
+
+    assert_eq!(vm.run_to_end(), vec![2]);
+}
+
+#[test]
+fn rewinds_when_blocked() {
+    // take [5]; out [5]; end
+    let mut vm = vm(&[12, 5, 4, 5, 99, 0]);
+
+    assert!(matches!(vm.run(), Outcome::NeedsInput));
+    assert_eq!(vm.pc, 0);
+    assert_eq!(vm.memory[5], 0);
+
+    vm.push_input(42);
+    assert_eq!(vm.run_to_end(), vec![42]);
+}
+
+#[test]
+fn rewinds_on_error() {
+    let mut vm = vm(&[104, 1, 13, 99]);
+
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+    assert!(matches!(vm.run(), Outcome::Error(Error::Overflow { .. })));
+    assert_eq!(vm.pc, 2);
+
+    // it stays stuck on the same instruction
+    assert!(matches!(vm.run(), Outcome::Error(Error::Overflow { .. })));
+    assert_eq!(vm.pc, 2);
+}
+
+#[test]
+#[should_panic]
+fn opcodes_have_two_digits() {
+    VM::<i64>::default().register_opcode(100, goto());
+}
+
+#[test]
+fn negative_instructions_are_illegal() {
+    // -1 used to decode as opcode 255
+    let mut vm = vm(&[-1, 0, 99]);
+    assert!(matches!(
+        vm.run(),
+        Outcome::Error(Error::IllegalInstruction { pc: 0 })
+    ));
+
+    let mut cached = CachedVM::new(&[-1, 0, 99]);
+    assert!(matches!(
+        cached.run(),
+        Outcome::Error(Error::IllegalInstruction { pc: 0 })
+    ));
+}
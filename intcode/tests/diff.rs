@@ -0,0 +1,43 @@
+use intcode::{diff, IntcodeMachine, VM};
+
+#[test]
+fn changed_ranges() {
+    let before = [1, 2, 3, 4, 5];
+    let after = [1, 9, 9, 4, 5, 0, 7];
+
+    let changes = diff::diff(&before, &after);
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].range(), 1..3);
+    assert_eq!(changes[0].before, vec![2, 3]);
+    assert_eq!(changes[0].after, vec![9, 9]);
+    assert_eq!(changes[1].range(), 6..7);
+    assert_eq!(changes[1].before, vec![0]);
+}
+
+#[test]
+fn day02_example() {
+    let tape = vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+
+    let mut vm = VM::default();
+    vm.load(&tape);
+    vm.run_to_end();
+
+    let changes = diff::diff(&tape, &vm.memory);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].after, vec![3500]);
+    assert_eq!(changes[1].after, vec![70]);
+
+    let report = diff::report(&tape, &vm.memory);
+    assert!(report.contains("0..1 (1 cells)"));
+    assert!(report.contains("3500"));
+}
+
+#[test]
+fn renders_ascii_and_instructions() {
+    let report = diff::report(&[0, 0, 0], &[72, 1101, 0]);
+
+    assert!(report.contains("'H'"));
+    assert!(report.contains("add 0, 0, [0]"));
+    assert_eq!(diff::report(&[1], &[1]), "No cells changed\n");
+}
@@ -0,0 +1,175 @@
+use std::sync::{Arc, Mutex};
+
+use intcode::{Hook, IntcodeMachine, Outcome, VM};
+
+/// inn [20]; add [20], 5, [21]; arb 7; out [21]; end
+static PROGRAM: &[i64] = &[3, 20, 1001, 20, 5, 21, 109, 7, 4, 21, 99];
+
+/// Writes down everything it's told, and pauses wherever it's asked to.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    break_at: Option<usize>,
+    pause_on_read: bool,
+    pause_on_write: bool,
+}
+
+impl Hook<i64> for Recorder {
+    fn before_instruction(&mut self, vm: &VM<i64>) -> bool {
+        self.break_at == Some(vm.pc)
+    }
+
+    fn on_read(&mut self, address: usize, value: &i64) -> bool {
+        self.events.push(format!("read {} = {}", address, value));
+        self.pause_on_read
+    }
+
+    fn on_write(&mut self, address: usize, old: &i64, new: &i64) -> bool {
+        self.events
+            .push(format!("write {}: {} -> {}", address, old, new));
+        self.pause_on_write
+    }
+
+    fn on_rb_change(&mut self, old: i64, new: i64) -> bool {
+        self.events.push(format!("rb {} -> {}", old, new));
+        false
+    }
+
+    fn on_input(&mut self, value: &i64) -> bool {
+        self.events.push(format!("input {}", value));
+        false
+    }
+
+    fn on_output(&mut self, value: &i64) -> bool {
+        self.events.push(format!("output {}", value));
+        false
+    }
+}
+
+fn watched(recorder: Recorder) -> (VM<i64>, Arc<Mutex<Recorder>>) {
+    let recorder = Arc::new(Mutex::new(recorder));
+
+    let mut vm = VM::default();
+    vm.load(PROGRAM);
+    vm.set_hook(Arc::clone(&recorder));
+    vm.watch_reads(20..22);
+    vm.watch_writes(20..22);
+
+    (vm, recorder)
+}
+
+#[test]
+fn reports_everything() {
+    let (mut vm, recorder) = watched(Recorder::default());
+    vm.push_input(3);
+
+    assert_eq!(vm.run_to_end(), vec![8]);
+    assert_eq!(
+        recorder.lock().unwrap().events,
+        vec![
+            "input 3",
+            "write 20: 0 -> 3",
+            "read 20 = 3",
+            "write 21: 0 -> 8",
+            "rb 0 -> 7",
+            "read 21 = 8",
+            "output 8",
+        ]
+    );
+}
+
+#[test]
+fn pauses_after_writes() {
+    let (mut vm, _recorder) = watched(Recorder {
+        pause_on_write: true,
+        ..Recorder::default()
+    });
+    vm.push_input(3);
+
+    // each pause lets the write finish first
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert_eq!((vm.pc, vm.memory[20]), (2, 3));
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert_eq!((vm.pc, vm.memory[21]), (6, 8));
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+    assert!(matches!(vm.run(), Outcome::Terminated));
+}
+
+#[test]
+fn breaks_before_instruction() {
+    let (mut vm, _recorder) = watched(Recorder {
+        break_at: Some(6),
+        ..Recorder::default()
+    });
+    vm.push_input(3);
+
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert_eq!((vm.pc, vm.rb), (6, 0));
+
+    // resuming runs the instruction it stopped on instead of stopping again
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+    assert_eq!(vm.rb, 7);
+    assert_eq!(vm.drain_output(), vec![8]);
+}
+
+#[test]
+fn breaks_once_on_blocked_input() {
+    let (mut vm, _recorder) = watched(Recorder {
+        break_at: Some(0),
+        ..Recorder::default()
+    });
+
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert!(matches!(vm.run(), Outcome::NeedsInput));
+    assert_eq!(vm.pc, 0);
+
+    // the inn it broke on is still the one waiting, so no second stop
+    vm.push_input(3);
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+    assert_eq!(vm.drain_output(), vec![8]);
+}
+
+#[test]
+fn keeps_pause_from_output() {
+    let (mut vm, _recorder) = watched(Recorder {
+        pause_on_read: true,
+        ..Recorder::default()
+    });
+    vm.push_input(3);
+
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert_eq!(vm.pc, 6);
+
+    // the out's read asks to pause, which comes once the output is handed over
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert_eq!(vm.pc, 10);
+    assert!(matches!(vm.run(), Outcome::Terminated));
+}
+
+#[test]
+fn load_forgets_pauses() {
+    let (mut vm, recorder) = watched(Recorder {
+        break_at: Some(0),
+        ..Recorder::default()
+    });
+
+    assert!(matches!(vm.run(), Outcome::Paused));
+    vm.load(PROGRAM);
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert!(matches!(vm.run(), Outcome::NeedsInput));
+
+    {
+        let mut recorder = recorder.lock().unwrap();
+        recorder.break_at = None;
+        recorder.pause_on_read = true;
+    }
+
+    vm.push_input(3);
+    assert!(matches!(vm.run(), Outcome::Paused));
+    assert!(matches!(vm.run(), Outcome::SentOutput));
+
+    // the pause the out asked for belonged to the old program
+    vm.load(PROGRAM);
+    assert!(matches!(vm.run(), Outcome::NeedsInput));
+}
@@ -0,0 +1,213 @@
+use intcode::{
+    asm::{assemble, AsmError},
+    disasm,
+    link::{link, stdlib, LinkError},
+    IntcodeMachine, VM,
+};
+
+fn run(source: &str, input: &[i64]) -> Vec<i64> {
+    let mut objects = stdlib();
+    objects.push(assemble(source).unwrap());
+
+    let mut vm = VM::default();
+    vm.load(&link(&objects).unwrap());
+
+    for &value in input {
+        vm.push_input(value);
+    }
+
+    vm.run_to_end()
+}
+
+fn ascii(output: &[i64]) -> String {
+    output.iter().map(|&value| value as u8 as char).collect()
+}
+
+#[test]
+fn print_number() {
+    let source = "
+        .export main
+        .import print_number
+
+        main:
+            inn [rb+2]
+            call print_number, 1
+            out '\\n'
+            jmp main
+    ";
+
+    let mut vm = VM::default();
+    let mut objects = stdlib();
+    objects.push(assemble(source).unwrap());
+    vm.load(&link(&objects).unwrap());
+
+    for &value in &[0, 7, 10, 1234567, -42, 999_999_999_999] {
+        vm.push_input(value);
+    }
+
+    let mut output = Vec::new();
+    while let intcode::Outcome::SentOutput = vm.run() {
+        output.extend(vm.drain_output());
+    }
+
+    assert_eq!(ascii(&output), "0\n7\n10\n1234567\n-42\n999999999999\n");
+}
+
+#[test]
+fn read_line() {
+    let source = "
+        .export main
+        .import read_line
+
+        main:
+            mov buffer, [rb+2]
+            call read_line, 1
+            out [rb+2]
+            out [buffer]
+            out [buffer+4]
+            ret
+
+        buffer:
+            data 0, 0, 0, 0, 0, 0, 0, 0
+    ";
+
+    let input: Vec<i64> = "hello\n".bytes().map(i64::from).collect();
+    assert_eq!(run(source, &input), vec![5, 'h' as i64, 'o' as i64]);
+}
+
+#[test]
+fn mul_loop() {
+    let source = "
+        .export main
+        .import mul_loop
+
+        main:
+            inn [rb+2]
+            inn [rb+3]
+            call mul_loop, 1
+            out [rb+2]
+            ret
+    ";
+
+    assert_eq!(run(source, &[6, 7]), vec![42]);
+    assert_eq!(run(source, &[6, -7]), vec![0]);
+}
+
+#[test]
+fn objects_are_relocated() {
+    let first = assemble(
+        "
+        .export main, counter
+        .import bump
+
+        main:
+            call bump, 1
+            call bump, 1
+            out [counter]
+            ret
+
+        counter:
+            data 40
+        ",
+    )
+    .unwrap();
+
+    let second = assemble(
+        "
+        .export bump
+        .import counter
+
+        bump:
+            add [counter], 1, [counter]
+            ret
+        ",
+    )
+    .unwrap();
+
+    for objects in &[[first.clone(), second.clone()], [second, first]] {
+        let mut vm = VM::default();
+        vm.load(&link(objects).unwrap());
+        assert_eq!(vm.run_to_end(), vec![42]);
+    }
+}
+
+#[test]
+fn link_errors() {
+    let main = assemble(".export main\nmain: ret").unwrap();
+    let missing = assemble(".import nowhere\ndata nowhere").unwrap();
+
+    assert_eq!(
+        link(&[missing]),
+        Err(LinkError::UndefinedSymbol {
+            name: "main".to_owned()
+        })
+    );
+
+    assert_eq!(
+        link(&[main.clone(), main]),
+        Err(LinkError::DuplicateSymbol {
+            name: "main".to_owned()
+        })
+    );
+}
+
+#[test]
+fn assembler_errors() {
+    assert_eq!(
+        assemble("add 1, 2, 3"),
+        Err(AsmError::ImmediateWrite { line: 1 })
+    );
+
+    assert_eq!(
+        assemble("\nfoo: jmp bar"),
+        Err(AsmError::UndefinedLabel {
+            line: 2,
+            name: "bar".to_owned()
+        })
+    );
+
+    assert_eq!(
+        assemble("out 1, 2"),
+        Err(AsmError::WrongOperandCount {
+            line: 1,
+            name: "out".to_owned(),
+            expected: 1,
+            found: 2
+        })
+    );
+}
+
+#[test]
+fn encoding() {
+    let object = assemble(
+        "
+        start:
+            add [rb-1], 5, [3]
+            mov 'a', [rb]
+            jmp .here
+        .here:
+            end
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        object.code,
+        vec![1201, -1, 5, 3, 21101, 97, 0, 0, 1105, 1, 11, 99]
+    );
+    assert_eq!(object.relocations, vec![10]);
+}
+
+#[test]
+fn disassembly_round_trips() {
+    let mut objects = stdlib();
+    objects.push(assemble(".export main\nmain: ret").unwrap());
+    let program = link(&objects).unwrap();
+
+    let source: String = disasm::listing(&program, 0, program.len())
+        .into_iter()
+        .map(|(_, line)| line + "\n")
+        .collect();
+
+    assert_eq!(assemble(&source).unwrap().code, program);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{Fake, Puzzle};
+use intcode::{
+    asm::assemble,
+    maze::{command, explore, Cell, Direction, MazeError},
+    IntcodeMachine, Outcome, VM,
+};
+
+/// The example from part two, with the droid starting at (3, 2).
+static MAP: &[&str] = &[
+    " ##   ", //
+    "#..## ", //
+    "#.#..#", //
+    "#.O.# ", //
+    " ###  ", //
+    "      ", //
+];
+
+const START: (i64, i64) = (3, 2);
+
+/// Plays the droid's side of the protocol over a map, without any Intcode.
+#[derive(Clone)]
+struct FakeDroid {
+    position: (i64, i64),
+    input: Vec<i64>,
+    output: Vec<i64>,
+}
+
+impl FakeDroid {
+    fn new() -> Self {
+        FakeDroid {
+            position: START,
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    fn at(x: i64, y: i64) -> char {
+        MAP[y as usize].as_bytes()[x as usize] as char
+    }
+}
+
+impl Puzzle for FakeDroid {
+    fn push_input(&mut self, value: i64) {
+        self.input.push(value);
+    }
+
+    fn drain_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn run(&mut self) -> Outcome {
+        if self.input.is_empty() {
+            return Outcome::NeedsInput;
+        }
+
+        let (dx, dy) = match self.input.remove(0) {
+            1 => (0, -1),
+            2 => (0, 1),
+            3 => (-1, 0),
+            _ => (1, 0),
+        };
+
+        let (x, y) = (self.position.0 + dx, self.position.1 + dy);
+        let status = match FakeDroid::at(x, y) {
+            '#' => 0,
+            'O' => 2,
+            _ => 1,
+        };
+
+        if status != 0 {
+            self.position = (x, y);
+        }
+
+        self.output.push(status);
+        Outcome::SentOutput
+    }
+}
+
+#[test]
+fn explore_example() {
+    let maze = explore(&Fake(FakeDroid::new())).unwrap();
+    let oxygen = maze.oxygen().unwrap();
+
+    assert_eq!(oxygen, (-1, 1));
+    assert_eq!(maze.shortest_path((0, 0), oxygen), Some(2));
+    assert_eq!(maze.fill_time(oxygen), 4);
+    assert_eq!(maze.render(), " ##   \n#..## \n#.#D.#\n#.O.# \n ###  \n");
+}
+
+#[test]
+fn forks_real_vm() {
+    // a droid boxed in by walls on every side
+    let program = assemble(
+        "
+    loop:
+        inn [direction]
+        out 0
+        jmp loop
+
+    direction: data 0
+    ",
+    )
+    .unwrap();
+
+    let mut vm: VM<i64> = VM::default();
+    vm.load(&program.code);
+
+    let maze = explore(&vm).unwrap();
+    assert_eq!(maze.cells.len(), 5);
+    assert_eq!(maze.oxygen(), None);
+    assert_eq!(maze.fill_time((0, 0)), 0);
+
+    // exploring doesn't touch the original
+    assert_eq!(vm.pc, 0);
+    assert_eq!(command(&mut vm, Direction::East), Ok(Cell::Wall));
+}
+
+#[test]
+fn silent_droid() {
+    let mut vm: VM<i64> = VM::default();
+    vm.load(&assemble("inn [0]\ninn [0]\nend").unwrap().code);
+
+    assert_eq!(explore(&vm), Err(MazeError::NoReply));
+}
+
+#[test]
+fn out_of_fuel() {
+    let mut vm: VM<i64> = VM::default();
+    vm.load(&assemble("loop:\njmp loop").unwrap().code);
+    vm.set_fuel(Some(100));
+
+    assert_eq!(explore(&vm), Err(MazeError::Paused));
+}
@@ -0,0 +1,134 @@
+use intcode::{
+    asm::assemble,
+    network::{Event, Network, NetworkError, Packet, NAT},
+    VM,
+};
+
+/// Three computers in a ring. Address 0 starts by sending y = 1 to address
+/// 1, and every computer passes what it gets on to the next address with y
+/// bumped by one. Address 2 passes it to the NAT instead.
+static RING: &str = "
+    inn [address]
+    jit [address], loop
+    out 1
+    out 0
+    out 1
+
+loop:
+    inn [x]
+    cme [x], -1, [empty]
+    jit [empty], loop
+    inn [y]
+    add [address], 1, [next]
+    cme [next], 3, [last]
+    jif [last], send
+    mov 255, [next]
+
+send:
+    add [y], 1, [y]
+    out [next]
+    out [x]
+    out [y]
+    jmp loop
+
+address: data 0
+x: data 0
+y: data 0
+empty: data 0
+next: data 0
+last: data 0
+";
+
+/// Sends one packet to the NAT a value per round, then listens forever.
+static SLOW: &str = "
+    inn [address]
+    out 255
+    inn [x]
+    inn [x]
+    out 7
+    inn [x]
+    out 9
+
+loop:
+    inn [x]
+    jmp loop
+
+address: data 0
+x: data 0
+";
+
+fn network(source: &str, size: usize) -> Network<VM<i64>> {
+    Network::new(&assemble(source).unwrap().code, size)
+}
+
+#[test]
+fn nat_wakes_idle_network() {
+    let mut network = network(RING, 3);
+
+    let first_nat = network
+        .run_until(|event| match event {
+            Event::Sent { to, packet, .. } if *to == NAT => Some(packet.y),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(first_nat, 3);
+
+    let mut wakes = Vec::new();
+    while wakes.len() < 3 {
+        for event in network.round().unwrap() {
+            if let Event::Woke(packet) = event {
+                wakes.push(packet.y);
+            }
+        }
+    }
+
+    assert_eq!(wakes, vec![3, 6, 9]);
+    assert_eq!(network.nat, Some(Packet { x: 0, y: 9 }));
+}
+
+#[test]
+fn rounds_are_deterministic() {
+    let mut first = network(RING, 3);
+    let mut second = network(RING, 3);
+
+    for _ in 0..20 {
+        assert_eq!(first.round(), second.round());
+    }
+}
+
+#[test]
+fn quiet_network_deadlocks() {
+    let mut network = network("inn [0]\nloop:\ninn [0]\njmp loop", 2);
+
+    assert_eq!(network.round(), Err(NetworkError::Deadlock));
+}
+
+#[test]
+fn unknown_address() {
+    let mut network = network("inn [0]\nout 7\nout 0\nout 0\nend", 2);
+
+    assert_eq!(
+        network.round(),
+        Err(NetworkError::UnknownAddress { from: 0, to: 7 })
+    );
+}
+
+#[test]
+fn half_sent_packet_isnt_idle() {
+    let mut network = network(SLOW, 1);
+    let packet = Packet { x: 7, y: 9 };
+
+    // nothing is sent or received for two rounds, but the packet is on its way
+    assert_eq!(network.round(), Ok(vec![]));
+    assert_eq!(network.round(), Ok(vec![]));
+    assert_eq!(
+        network.round(),
+        Ok(vec![Event::Sent {
+            from: 0,
+            to: NAT,
+            packet: packet.clone()
+        }])
+    );
+    assert_eq!(network.round(), Ok(vec![Event::Woke(packet)]));
+}
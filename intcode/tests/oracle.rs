@@ -0,0 +1,119 @@
+use intcode::{
+    asm::assemble,
+    beam::{count_pulled, fit_square, pulled},
+    IntcodeMachine, QueryError, QueryOracle, VM,
+};
+
+/// A beam that pulls wherever 2y <= 3x <= 4y.
+static BEAM: &str = "
+    inn [x]
+    inn [y]
+    mul [x], 3, [x]
+    mul [y], 2, [low]
+    mul [y], 4, [high]
+    cml [x], [low], [low]
+    cml [high], [x], [high]
+    add [low], [high], [x]
+    cme [x], 0, [x]
+    out [x]
+    end
+
+x: data 0
+y: data 0
+low: data 0
+high: data 0
+";
+
+fn in_beam(x: i64, y: i64) -> bool {
+    2 * y <= 3 * x && 3 * x <= 4 * y
+}
+
+fn oracle() -> QueryOracle<VM<i64>> {
+    QueryOracle::from_tape(&assemble(BEAM).unwrap().code)
+}
+
+#[test]
+fn memoises_queries() {
+    let mut oracle = oracle();
+
+    assert_eq!(oracle.query(&[3, 3]), Ok(vec![1]));
+    assert_eq!(oracle.query(&[3, 3]), Ok(vec![1]));
+    assert_eq!(oracle.ask(&[1, 3]), Ok(0));
+    assert_eq!((oracle.runs, oracle.hits), (2, 1));
+
+    // failures aren't remembered
+    assert_eq!(oracle.query(&[3]), Err(QueryError::Starved));
+    assert_eq!(oracle.runs, 2);
+}
+
+#[test]
+fn counts_beam() {
+    let mut oracle = oracle();
+    let expected = (0..20)
+        .flat_map(|y| (0..20).map(move |x| (x, y)))
+        .filter(|&(x, y)| in_beam(x, y))
+        .count();
+
+    assert_eq!(count_pulled(&mut oracle, 20), Ok(expected));
+    assert!(pulled(&mut oracle, 4, 5).unwrap());
+    assert_eq!(oracle.runs, 400);
+}
+
+#[test]
+fn fits_square() {
+    let size = 10;
+    let fits = |x: i64, y: i64| {
+        in_beam(x, y)
+            && in_beam(x + size - 1, y)
+            && in_beam(x, y + size - 1)
+            && in_beam(x + size - 1, y + size - 1)
+    };
+
+    let expected = (0..200)
+        .flat_map(|y| (0..200).map(move |x| (x, y)))
+        .find(|&(x, y)| fits(x, y));
+
+    let mut oracle = oracle();
+    let found = fit_square(&mut oracle, size, 200).unwrap();
+
+    assert_eq!(found, expected);
+    assert!(oracle.runs < 200, "took {} queries", oracle.runs);
+
+    assert_eq!(fit_square(&mut oracle, size, 20), Ok(None));
+
+    let runs = oracle.runs;
+    assert_eq!(fit_square(&mut oracle, 0, 200), Ok(None));
+    assert_eq!(fit_square(&mut oracle, -3, 200), Ok(None));
+    assert_eq!(oracle.runs, runs);
+}
+
+#[test]
+fn coordinates_must_fit() {
+    let code: Vec<i32> = assemble(BEAM)
+        .unwrap()
+        .code
+        .into_iter()
+        .map(|word| word as i32)
+        .collect();
+    let mut oracle: QueryOracle<VM<i32>> = QueryOracle::from_tape(&code);
+    let far = i64::from(i32::MAX) + 1;
+
+    assert_eq!(
+        pulled(&mut oracle, far, 0),
+        Err(QueryError::InputTooLarge(far))
+    );
+    assert_eq!(
+        pulled(&mut oracle, 3, -far - 1),
+        Err(QueryError::InputTooLarge(-far - 1))
+    );
+    assert_eq!(oracle.runs, 0);
+}
+
+#[test]
+fn paused_query() {
+    let mut vm: VM<i64> = VM::default();
+    vm.load(&assemble("loop:\njmp loop").unwrap().code);
+    vm.set_fuel(Some(100));
+
+    assert_eq!(QueryOracle::new(vm).query(&[]), Err(QueryError::Paused));
+}
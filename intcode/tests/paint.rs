@@ -0,0 +1,71 @@
+use intcode::{
+    asm::assemble,
+    paint::{Color, Heading, Hull, PaintError, Robot},
+    IntcodeMachine, VM,
+};
+
+/// A program that reads the camera before every move and answers with a
+/// fixed list of (colour, turn) pairs.
+fn scripted(moves: &[(i64, i64)]) -> VM<i64> {
+    let mut source = String::new();
+
+    for (color, turn) in moves {
+        source.push_str(&format!("inn [camera]\nout {}\nout {}\n", color, turn));
+    }
+
+    source.push_str("end\ncamera: data 0\n");
+
+    let mut vm = VM::default();
+    vm.load(&assemble(&source).unwrap().code);
+    vm
+}
+
+#[test]
+fn puzzle_example() {
+    let mut vm = scripted(&[(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)]);
+    let mut hull = Hull::default();
+    let mut robot = Robot::default();
+
+    robot.run(&mut vm, &mut hull).unwrap();
+
+    assert_eq!(hull.painted(), 6);
+    assert_eq!(robot.position, (0, -1));
+    assert_eq!(robot.heading, Heading::Left);
+    assert_eq!(hull.render(), "    ##\n    ##\n####  \n");
+    assert_eq!(hull.to_pbm(), "P1\n3 3\n1 1 0\n1 1 0\n0 0 1\n");
+}
+
+#[test]
+fn starting_panel() {
+    // paint a white starting panel black, and the next one white
+    let mut vm = scripted(&[(0, 1), (1, 1)]);
+    let mut hull = Hull::default();
+    hull.paint((0, 0), Color::White);
+
+    Robot::default().run(&mut vm, &mut hull).unwrap();
+
+    assert_eq!(hull.color((0, 0)), Color::Black);
+    assert_eq!(hull.color((1, 0)), Color::White);
+    assert_eq!(hull.bounds(), Some(((1, 0), (1, 0))));
+}
+
+#[test]
+fn invalid_output() {
+    let mut vm = scripted(&[(2, 0)]);
+
+    assert_eq!(
+        Robot::default().run(&mut vm, &mut Hull::default()),
+        Err(PaintError::InvalidColor(Some(2)))
+    );
+}
+
+#[test]
+fn out_of_fuel() {
+    let mut vm = scripted(&[(1, 0); 10]);
+    vm.set_fuel(Some(5));
+
+    assert_eq!(
+        Robot::default().run(&mut vm, &mut Hull::default()),
+        Err(PaintError::Paused)
+    );
+}
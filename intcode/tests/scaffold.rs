@@ -0,0 +1,151 @@
+mod common;
+
+use common::{Fake, Puzzle};
+use intcode::{
+    paint::Heading,
+    scaffold::{compress, format_path, navigate, Pose, Scaffold, ScaffoldError, MAX_LENGTH},
+    Outcome,
+};
+
+static CAMERA: &str = "\
+..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..
+";
+
+static WINDING: &str = "\
+#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......
+";
+
+const WINDING_PATH: &str = "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2";
+
+#[test]
+fn alignment() {
+    let scaffold = Scaffold::parse(CAMERA);
+
+    assert_eq!(
+        scaffold.intersections(),
+        vec![(2, 2), (2, 4), (6, 4), (10, 4)]
+    );
+    assert_eq!(scaffold.alignment(), 76);
+    assert_eq!(
+        scaffold.robot(),
+        Some(Pose {
+            position: (10, 6),
+            heading: Heading::Up,
+        })
+    );
+    assert_eq!(scaffold.to_string(), CAMERA);
+}
+
+#[test]
+fn path_and_routine() {
+    let path = Scaffold::parse(WINDING).path().unwrap();
+    assert_eq!(format_path(&path), WINDING_PATH);
+
+    let routine = compress(&path, MAX_LENGTH).unwrap();
+    assert_eq!(routine.expand(), path);
+    assert!(routine.functions.len() <= 3);
+
+    for line in routine.lines() {
+        assert!(line.len() <= MAX_LENGTH, "{} is too long", line);
+    }
+
+    // nothing fits when lines can only hold a single turn
+    assert_eq!(compress(&path, 1), None);
+}
+
+#[test]
+fn tumbling_robot() {
+    let scaffold = Scaffold::parse("..#..\n.#X#.\n..#..\n");
+
+    assert_eq!(scaffold.robot(), None);
+    assert_eq!(scaffold.path(), Err(ScaffoldError::NoRobot));
+}
+
+#[test]
+fn ring_never_ends() {
+    let ring = Scaffold::parse("#####\n#...#\n^...#\n#...#\n#####\n");
+    assert_eq!(ring.path(), Err(ScaffoldError::Loop));
+
+    // starting partway along an edge doesn't matter
+    let edge = Scaffold::parse("#>###\n#...#\n#####\n");
+    assert_eq!(edge.path(), Err(ScaffoldError::Loop));
+}
+
+/// Draws `WINDING`, reads a routine back, and reports dust if the routine
+/// walks the right path.
+#[derive(Default)]
+struct FakeRobot {
+    started: bool,
+    finished: bool,
+    input: String,
+    output: Vec<i64>,
+}
+
+impl Puzzle for FakeRobot {
+    fn push_input(&mut self, value: i64) {
+        self.input.push(value as u8 as char);
+    }
+
+    fn drain_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn run(&mut self) -> Outcome {
+        if self.finished {
+            return Outcome::Terminated;
+        }
+
+        if !self.started {
+            self.started = true;
+            let picture = format!("{}\nMain:\n", WINDING);
+            self.output.extend(picture.bytes().map(i64::from));
+            return Outcome::SentOutput;
+        }
+
+        let lines: Vec<&str> = self.input.lines().collect();
+        if lines.len() < 5 {
+            return Outcome::NeedsInput;
+        }
+
+        let walked: Vec<&str> = lines[0]
+            .split(',')
+            .map(|name| lines[(name.as_bytes()[0] - b'A') as usize + 1])
+            .collect();
+
+        // anything past ASCII is the dust, so a wrong path reports none
+        let dust = if walked.join(",") == WINDING_PATH {
+            1042
+        } else {
+            128
+        };
+
+        self.finished = true;
+        self.output.push(dust);
+        Outcome::SentOutput
+    }
+}
+
+#[test]
+fn navigates() {
+    assert_eq!(navigate(&mut Fake(FakeRobot::default())), Ok(Some(1042)));
+}
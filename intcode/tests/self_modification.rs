@@ -0,0 +1,61 @@
+use intcode::{CellKind, IntcodeMachine, SelfModification, VM};
+
+/// Runs `out 9` first, then goes back and rewrites its operand and then its
+/// opcode, turning it into `end` for the second time round.
+static REWRITER: &[i64] = &[
+    1105, 1, 11, // jmp 11
+    1101, 0, 3, 12, // mov 3, [12]
+    1101, 0, 99, 11, // mov 99, [11]
+    104, 9, // out 9
+    1105, 1, 3, // jmp 3
+];
+
+#[test]
+fn detects_rewritten_code() {
+    let mut vm = VM::default();
+    vm.detect_self_modification = true;
+    vm.load(REWRITER);
+
+    assert_eq!(vm.run_to_end(), vec![9]);
+    assert_eq!(
+        vm.self_modifications,
+        vec![
+            SelfModification {
+                pc: 3,
+                target: 12,
+                kind: CellKind::Operand,
+                value: 3,
+            },
+            SelfModification {
+                pc: 7,
+                target: 11,
+                kind: CellKind::Opcode,
+                value: 99,
+            },
+        ]
+    );
+}
+
+#[test]
+fn ignores_data_and_unrun_code() {
+    // the same writes, before the instruction they land on has ever run
+    let mut vm = VM::default();
+    vm.detect_self_modification = true;
+    vm.load(&[
+        1101, 0, 3, 11, // mov 3, [11]
+        1101, 0, 99, 10, // mov 99, [10]
+        104, 9,  // out 9
+        99, // end
+        0,  // data
+    ]);
+
+    assert_eq!(vm.run_to_end(), vec![9]);
+    assert_eq!(vm.memory[11], 3);
+    assert!(vm.self_modifications.is_empty());
+
+    // and nothing is looked for unless it's asked for
+    let mut vm = VM::default();
+    vm.load(REWRITER);
+    vm.run_to_end();
+    assert!(vm.self_modifications.is_empty());
+}
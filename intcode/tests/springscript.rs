@@ -0,0 +1,191 @@
+mod common;
+
+use common::{Fake, Puzzle};
+use intcode::{
+    springscript::{read_hull, run, search, Mode, Report, Script, ScriptError},
+    Outcome,
+};
+
+/// Hulls like the ones from part one. Running off the end counts as ground.
+static HULLS: &[&str] = &[
+    "#####.#..########",
+    "#####...#########",
+    "#####..#.########",
+];
+
+const DAMAGE: i64 = 19_354_437;
+
+/// Reads a script and runs it over `HULLS` like the real droid would,
+/// without any Intcode.
+#[derive(Clone, Default)]
+struct FakeDroid {
+    prompted: bool,
+    finished: bool,
+    input: String,
+    output: Vec<i64>,
+}
+
+impl FakeDroid {
+    fn say(&mut self, text: &str) {
+        self.output.extend(text.bytes().map(i64::from));
+    }
+
+    /// Whether the script gets across `hull`.
+    fn survives(script: &[Vec<&str>], hull: &[u8], sensors: usize) -> bool {
+        let ground = |index: usize| hull.get(index) != Some(&b'.');
+        let mut position = 0;
+
+        while position < hull.len() {
+            let (mut t, mut j) = (false, false);
+
+            for line in script {
+                let source = match line[1] {
+                    "T" => t,
+                    "J" => j,
+                    sensor => {
+                        let index = (sensor.as_bytes()[0] - b'A') as usize;
+                        assert!(index < sensors);
+                        ground(position + index + 1)
+                    }
+                };
+
+                let target = if line[2] == "T" { &mut t } else { &mut j };
+                *target = match line[0] {
+                    "AND" => source && *target,
+                    "OR" => source || *target,
+                    _ => !source,
+                };
+            }
+
+            position += if j { 4 } else { 1 };
+
+            if !ground(position) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Puzzle for FakeDroid {
+    fn push_input(&mut self, value: i64) {
+        self.input.push(value as u8 as char);
+    }
+
+    fn drain_output(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn run(&mut self) -> Outcome {
+        if self.finished {
+            return Outcome::Terminated;
+        }
+
+        if !self.prompted {
+            self.prompted = true;
+            self.say("Input instructions:\n");
+            return Outcome::SentOutput;
+        }
+
+        let input = self.input.clone();
+        let lines: Vec<Vec<&str>> = input
+            .lines()
+            .map(|line| line.split(' ').collect())
+            .collect();
+
+        let sensors = match lines.last().map(|line| line[0]) {
+            Some("WALK") => 4,
+            Some("RUN") => 9,
+            _ => return Outcome::NeedsInput,
+        };
+
+        assert!(lines.len() <= 16, "script too long");
+        let script = &lines[..lines.len() - 1];
+
+        self.finished = true;
+
+        match HULLS
+            .iter()
+            .find(|hull| !FakeDroid::survives(script, hull.as_bytes(), sensors))
+        {
+            Some(hull) => self.say(&format!(
+                "\nDidn't make it across:\n\n.................\n@................\n{}\n\n",
+                hull
+            )),
+            None => self.output.push(DAMAGE),
+        }
+
+        Outcome::SentOutput
+    }
+}
+
+#[test]
+fn compile_expression() {
+    let script = Script::compile("!(A & B & C) & D", Mode::Walk).unwrap();
+
+    assert_eq!(
+        script.to_string(),
+        "NOT A J\nAND D J\nNOT B T\nAND D T\nOR T J\nNOT C T\nAND D T\nOR T J\nWALK\n"
+    );
+
+    // a single sensor on its own doesn't need T
+    let script = Script::compile("!A | D", Mode::Walk).unwrap();
+    assert_eq!(script.to_string(), "NOT A J\nOR D J\nWALK\n");
+
+    // contradictions and duplicates drop out
+    let script = Script::compile("(A & !A) | (B & C) | (C & B & D)", Mode::Run).unwrap();
+    assert_eq!(script.to_string(), "OR B J\nAND C J\nRUN\n");
+}
+
+#[test]
+fn checks_limits() {
+    assert_eq!(
+        Script::compile("!A & E", Mode::Walk),
+        Err(ScriptError::SensorOutOfRange {
+            sensor: 'E',
+            mode: Mode::Walk
+        })
+    );
+
+    assert_eq!(
+        Script::compile("!A&B | !B&C | !C&D | !D&E | !E&F | !F&G", Mode::Run),
+        Err(ScriptError::TooLong { count: 17 })
+    );
+
+    assert!(matches!(
+        Script::compile("A & (B", Mode::Walk),
+        Err(ScriptError::Syntax { position: 6, .. })
+    ));
+}
+
+#[test]
+fn streams_script() {
+    let script = Script::compile("!(A & B & C) & D", Mode::Walk).unwrap();
+    assert_eq!(
+        run(&mut Fake::<FakeDroid>::default(), &script),
+        Ok(Report::Damage(DAMAGE))
+    );
+
+    let script = Script::compile("!A", Mode::Walk).unwrap();
+    match run(&mut Fake::<FakeDroid>::default(), &script).unwrap() {
+        Report::Fell(frame) => {
+            let hull = read_hull(&frame).unwrap();
+            assert_eq!(hull.len(), HULLS[0].len());
+            assert!(hull[0]);
+            assert!(!hull[5]);
+        }
+        report => panic!("droid didn't fall: {:?}", report),
+    }
+}
+
+#[test]
+fn finds_script() {
+    for &mode in &[Mode::Walk, Mode::Run] {
+        let (script, damage) = search(&Fake::<FakeDroid>::default(), mode, 10).unwrap();
+
+        assert_eq!(damage, DAMAGE);
+        assert_eq!(script.mode, mode);
+        assert!(script.instructions.len() <= 15);
+    }
+}
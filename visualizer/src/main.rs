@@ -0,0 +1,253 @@
+//! Terminal visualizer for Intcode programs.
+//!
+//! Shows memory as a heatmap of recent reads and writes, the instructions
+//! around pc, the cells around the relative base, and the I/O queues.
+//!
+//!     cargo run -p visualizer -- day09/input.txt 1
+//!
+//! The first argument is a tape and any others are queued as input.
+//!
+//! Keys: space runs or pauses, `s` steps one instruction, `+` and `-` change
+//! speed, `i` queues an input, page up and page down scroll memory, `r`
+//! restarts and `q` quits.
+
+mod recorder;
+mod ui;
+
+use std::{
+    fs,
+    io::{self, Write},
+    panic,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute, terminal,
+};
+use intcode::{Error, Outcome, VM};
+
+use recorder::Recorder;
+
+/// Instructions run per frame at each speed setting.
+static SPEEDS: &[u64] = &[1, 10, 100, 1_000, 10_000, 100_000];
+
+const FRAME_TIME: Duration = Duration::from_millis(33);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ready,
+    NeedsInput,
+    Halted,
+    Faulted(Error),
+}
+
+pub struct App {
+    pub program: Vec<i64>,
+
+    /// The inputs queued on the command line, for restarting with.
+    pub inputs: Vec<i64>,
+
+    pub vm: VM<i64>,
+    pub recorder: Arc<Mutex<Recorder>>,
+    pub status: Status,
+    pub running: bool,
+    pub speed: usize,
+    pub outputs: Vec<i64>,
+
+    /// An input being typed, if the input prompt is open.
+    pub entry: Option<String>,
+
+    /// The first row of memory shown in the heatmap.
+    pub scroll: usize,
+
+    /// Set when the last run paused on a hook. The VM runs the instruction
+    /// it paused on without asking the hook again, so that one has to be
+    /// counted by hand.
+    resuming: bool,
+}
+
+impl App {
+    fn new(program: Vec<i64>, inputs: &[i64]) -> Self {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+
+        let mut vm = VM::default();
+        vm.load_memory(program.clone());
+        vm.input.extend(inputs);
+        vm.watch_reads(0..usize::MAX);
+        vm.watch_writes(0..usize::MAX);
+        vm.set_hook(recorder.clone());
+
+        App {
+            program,
+            inputs: inputs.to_vec(),
+            vm,
+            recorder,
+            status: Status::Ready,
+            running: false,
+            speed: 2,
+            outputs: Vec::new(),
+            entry: None,
+            scroll: 0,
+            resuming: false,
+        }
+    }
+
+    /// Starts the program over with the inputs it was started with. Ones
+    /// typed in since then are dropped.
+    fn restart(&mut self) {
+        let speed = self.speed;
+
+        *self = App::new(self.program.clone(), &self.inputs);
+        self.speed = speed;
+    }
+
+    /// Runs up to `count` instructions, stopping early if the program needs
+    /// input, halts or faults.
+    fn advance(&mut self, count: u64) {
+        if self.status != Status::Ready || count == 0 {
+            return;
+        }
+
+        {
+            let mut recorder = self.recorder.lock().unwrap();
+
+            if self.resuming {
+                recorder.executed(self.vm.pc);
+                recorder.budget = count - 1;
+            } else {
+                recorder.budget = count;
+            }
+        }
+
+        self.resuming = false;
+
+        loop {
+            match self.vm.run_partial() {
+                Outcome::SentOutput => self.outputs.push(self.vm.get_output()),
+                Outcome::Paused => {
+                    self.resuming = true;
+                    break;
+                }
+                Outcome::NeedsInput => {
+                    self.status = Status::NeedsInput;
+                    break;
+                }
+                Outcome::Terminated => {
+                    self.status = Status::Halted;
+                    break;
+                }
+                Outcome::Error(error) => {
+                    self.status = Status::Faulted(error);
+                    break;
+                }
+            }
+        }
+
+        if self.status != Status::Ready {
+            self.running = false;
+        }
+    }
+
+    /// Handles a key press. Returns false when it's time to quit.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if let Some(entry) = &mut self.entry {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '-' => entry.push(c),
+                KeyCode::Backspace => {
+                    entry.pop();
+                }
+                KeyCode::Enter => {
+                    if let Ok(value) = entry.parse() {
+                        self.vm.put_input(value);
+
+                        if self.status == Status::NeedsInput {
+                            self.status = Status::Ready;
+                        }
+                    }
+
+                    self.entry = None;
+                }
+                KeyCode::Esc => self.entry = None,
+                _ => {}
+            }
+
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') => self.running = !self.running,
+            KeyCode::Char('s') | KeyCode::Right => {
+                self.running = false;
+                self.advance(1);
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.speed = (self.speed + 1).min(SPEEDS.len() - 1)
+            }
+            KeyCode::Char('-') => self.speed = self.speed.saturating_sub(1),
+            KeyCode::Char('i') => self.entry = Some(String::new()),
+            KeyCode::Char('r') => self.restart(),
+            KeyCode::PageDown => self.scroll += 4,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(4),
+            _ => {}
+        }
+
+        true
+    }
+}
+
+fn run(app: &mut App) -> io::Result<()> {
+    let mut stdout = io::stdout();
+
+    loop {
+        ui::draw(app, &mut stdout)?;
+
+        if event::poll(FRAME_TIME)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.key(key) {
+                    return Ok(());
+                }
+            }
+        }
+
+        if app.running {
+            app.advance(SPEEDS[app.speed]);
+        }
+    }
+}
+
+fn restore_terminal() {
+    let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().expect("usage: visualizer <tape> [inputs...]");
+    let inputs: Vec<i64> = args
+        .map(|arg| arg.parse().expect("inputs must be numbers"))
+        .collect();
+
+    let tape = fs::read_to_string(&path)?;
+    let mut app = App::new(VM::decode_tape(&tape), &inputs);
+
+    // put the terminal back before printing the panic message, or it ends up
+    // garbled in the alternate screen
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = run(&mut app);
+
+    restore_terminal();
+    io::stdout().flush()?;
+    result
+}
@@ -0,0 +1,53 @@
+use std::collections::{HashMap, VecDeque};
+
+use intcode::{Hook, VM};
+
+/// How many recently executed instructions to remember.
+const TRAIL_LEN: usize = 6;
+
+/// Watches the VM run, remembering when each cell was last read or written
+/// and which instructions ran most recently. It also acts as the VM's fuel,
+/// pausing once it has let `budget` instructions through.
+#[derive(Default)]
+pub struct Recorder {
+    /// Instructions executed so far, used as a clock for the heatmap.
+    pub ticks: u64,
+    pub budget: u64,
+    pub reads: HashMap<usize, u64>,
+    pub writes: HashMap<usize, u64>,
+    pub trail: VecDeque<usize>,
+}
+
+impl Recorder {
+    pub fn executed(&mut self, pc: usize) {
+        self.ticks += 1;
+
+        if self.trail.len() == TRAIL_LEN {
+            self.trail.pop_front();
+        }
+
+        self.trail.push_back(pc);
+    }
+}
+
+impl Hook<i64> for Recorder {
+    fn before_instruction(&mut self, vm: &VM<i64>) -> bool {
+        if self.budget == 0 {
+            return true;
+        }
+
+        self.budget -= 1;
+        self.executed(vm.pc);
+        false
+    }
+
+    fn on_read(&mut self, address: usize, _value: &i64) -> bool {
+        self.reads.insert(address, self.ticks);
+        false
+    }
+
+    fn on_write(&mut self, address: usize, _old: &i64, _new: &i64) -> bool {
+        self.writes.insert(address, self.ticks);
+        false
+    }
+}
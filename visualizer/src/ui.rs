@@ -0,0 +1,250 @@
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+use intcode::disasm;
+
+use crate::{recorder::Recorder, App, Status, SPEEDS};
+
+const LEFT_WIDTH: u16 = 44;
+const HEAT_LEFT: u16 = LEFT_WIDTH + 2;
+
+/// Width of the address labels down the side of the heatmap.
+const LABEL_WIDTH: u16 = 7;
+
+/// Rows at the bottom for the I/O queues and key help.
+const FOOTER_HEIGHT: u16 = 4;
+
+/// How many cells either side of the relative base to show.
+const RB_BEFORE: i64 = 2;
+const RB_AFTER: i64 = 6;
+
+/// Shades for cells touched within 64, 1024 and 16384 instructions.
+const WRITE_COLORS: [Color; 3] = [Color::Red, Color::DarkRed, Color::DarkMagenta];
+const READ_COLORS: [Color; 3] = [Color::Green, Color::DarkGreen, Color::DarkCyan];
+
+fn shade(now: u64, last: Option<&u64>) -> Option<usize> {
+    match now - last? {
+        age if age < 64 => Some(0),
+        age if age < 1024 => Some(1),
+        age if age < 16384 => Some(2),
+        _ => None,
+    }
+}
+
+fn line(out: &mut impl Write, x: u16, y: u16, text: &str) -> io::Result<()> {
+    queue!(out, MoveTo(x, y), Print(text))
+}
+
+pub fn draw(app: &App, out: &mut impl Write) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    queue!(out, Clear(ClearType::All))?;
+
+    let recorder = app.recorder.lock().unwrap();
+
+    let status = match app.status {
+        Status::Ready if app.running => "running".to_owned(),
+        Status::Ready => "paused".to_owned(),
+        Status::NeedsInput => "waiting for input, press i".to_owned(),
+        Status::Halted => "halted".to_owned(),
+        Status::Faulted(error) => format!("faulted: {:?}", error),
+    };
+
+    queue!(out, SetAttribute(Attribute::Bold))?;
+    line(
+        out,
+        0,
+        0,
+        &format!(
+            "pc {:<6} rb {:<6} executed {:<10} speed {}/frame   {}",
+            app.vm.pc, app.vm.rb, recorder.ticks, SPEEDS[app.speed], status
+        ),
+    )?;
+    queue!(out, SetAttribute(Attribute::Reset))?;
+
+    let body_height = height.saturating_sub(FOOTER_HEIGHT + 2);
+    draw_code(app, &recorder, out, body_height)?;
+    draw_memory(app, &recorder, out, width, body_height)?;
+    draw_footer(app, out, height)?;
+
+    out.flush()
+}
+
+fn draw_code(app: &App, recorder: &Recorder, out: &mut impl Write, height: u16) -> io::Result<()> {
+    let memory = &app.vm.memory;
+    let mut y = 2;
+
+    queue!(out, SetAttribute(Attribute::Underlined))?;
+    line(out, 0, y, "Code")?;
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    y += 1;
+
+    // the instructions that just ran, oldest first, not counting the one
+    // about to run again
+    queue!(out, SetForegroundColor(Color::DarkGrey))?;
+    for &pc in recorder.trail.iter().filter(|&&pc| pc != app.vm.pc) {
+        let text = disasm::listing(memory, pc, 1)
+            .pop()
+            .map(|(_, text)| text)
+            .unwrap_or_default();
+
+        line(out, 0, y, &format!("  {:>6}  {}", pc, text))?;
+        y += 1;
+    }
+    queue!(out, ResetColor)?;
+
+    let rb_section = (RB_BEFORE + RB_AFTER + 3) as u16;
+    let upcoming = height.saturating_sub(y + rb_section) as usize;
+
+    for (index, (address, text)) in disasm::listing(memory, app.vm.pc, upcoming)
+        .into_iter()
+        .enumerate()
+    {
+        let text = format!(
+            "{} {:>6}  {}",
+            if index == 0 { ">" } else { " " },
+            address,
+            text
+        );
+
+        if index == 0 {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+            line(
+                out,
+                0,
+                y,
+                &format!("{:<width$}", text, width = LEFT_WIDTH as usize),
+            )?;
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        } else {
+            line(out, 0, y, &text)?;
+        }
+
+        y += 1;
+    }
+
+    y += 1;
+    queue!(out, SetAttribute(Attribute::Underlined))?;
+    line(out, 0, y, "Relative base")?;
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    y += 1;
+
+    for offset in -RB_BEFORE..=RB_AFTER {
+        let address = app.vm.rb + offset;
+        let label = match offset {
+            0 => "[rb]".to_owned(),
+            offset if offset < 0 => format!("[rb{}]", offset),
+            offset => format!("[rb+{}]", offset),
+        };
+
+        let value = if address < 0 {
+            "invalid".to_owned()
+        } else {
+            let value = memory.get(address as usize).copied().unwrap_or(0);
+            format!("{:>6} = {}", address, value)
+        };
+
+        line(out, 2, y, &format!("{:<8}{}", label, value))?;
+        y += 1;
+    }
+
+    Ok(())
+}
+
+fn draw_memory(
+    app: &App,
+    recorder: &Recorder,
+    out: &mut impl Write,
+    width: u16,
+    height: u16,
+) -> io::Result<()> {
+    let columns = width.saturating_sub(HEAT_LEFT + LABEL_WIDTH) as usize / 8 * 8;
+    if columns == 0 {
+        return Ok(());
+    }
+
+    let memory_len = app.vm.memory.len();
+    let rows = height.saturating_sub(3) as usize;
+    let last_row = memory_len.div_ceil(columns);
+    let first_row = app.scroll.min(last_row.saturating_sub(1));
+
+    queue!(out, SetAttribute(Attribute::Underlined))?;
+    line(out, HEAT_LEFT, 2, &format!("Memory ({} cells)", memory_len))?;
+    queue!(out, SetAttribute(Attribute::Reset))?;
+
+    let rb = usize::try_from(app.vm.rb).ok();
+
+    for row in 0..rows {
+        let start = (first_row + row) * columns;
+        if start >= memory_len {
+            break;
+        }
+
+        let y = 3 + row as u16;
+        queue!(out, SetForegroundColor(Color::DarkGrey))?;
+        line(out, HEAT_LEFT, y, &format!("{:>6} ", start))?;
+
+        for address in start..(start + columns).min(memory_len) {
+            let write = shade(recorder.ticks, recorder.writes.get(&address));
+            let read = shade(recorder.ticks, recorder.reads.get(&address));
+
+            let (color, glyph) = if address == app.vm.pc {
+                (Color::White, '@')
+            } else if Some(address) == rb {
+                (Color::Yellow, 'R')
+            } else {
+                // the most recent access wins, and writes win ties
+                match (write, read) {
+                    (Some(w), Some(r)) if r < w => (READ_COLORS[r], '\u{2588}'),
+                    (Some(w), _) => (WRITE_COLORS[w], '\u{2588}'),
+                    (None, Some(r)) => (READ_COLORS[r], '\u{2588}'),
+                    (None, None) => (Color::DarkGrey, '\u{b7}'),
+                }
+            };
+
+            queue!(out, SetForegroundColor(color), Print(glyph))?;
+        }
+    }
+
+    queue!(out, ResetColor)
+}
+
+fn draw_footer(app: &App, out: &mut impl Write, height: u16) -> io::Result<()> {
+    let top = height.saturating_sub(FOOTER_HEIGHT);
+
+    let input: Vec<String> = app.vm.input.iter().map(|value| value.to_string()).collect();
+    line(out, 0, top, &format!("Input:  {}", input.join(", ")))?;
+
+    let shown = app.outputs.len().saturating_sub(16);
+    let output: Vec<String> = app.outputs[shown..]
+        .iter()
+        .map(|value| value.to_string())
+        .collect();
+    line(
+        out,
+        0,
+        top + 1,
+        &format!(
+            "Output: {} ({} total)",
+            output.join(", "),
+            app.outputs.len()
+        ),
+    )?;
+
+    let help = match &app.entry {
+        Some(entry) => format!("Queue input: {}_   (enter to queue, esc to cancel)", entry),
+        None => "space run/pause  s step  +/- speed  i input  pgup/pgdn scroll  r restart  q quit"
+            .to_owned(),
+    };
+
+    queue!(out, SetForegroundColor(Color::DarkGrey))?;
+    line(out, 0, top + 3, &help)?;
+    queue!(out, ResetColor)
+}